@@ -0,0 +1,65 @@
+//! Measures how the time per decision grows with the number of variables,
+//! without clauses, where the search only makes decisions, and on easy
+//! random 3-SAT instances. Run with `cargo bench --bench decisions`.
+
+use rand::prelude::*;
+use rsat::cdcl::{BranchingHeuristic, Solver, SolverOptions};
+use solhop_types::{Lit, Solution, Var};
+use std::time::Instant;
+
+fn random_3sat(n_vars: usize, ratio: f64, rng: &mut StdRng) -> Vec<Vec<Lit>> {
+    let n_clauses = (n_vars as f64 * ratio) as usize;
+    (0..n_clauses)
+        .map(|_| {
+            (0..3)
+                .map(|_| Lit::new(Var::new(rng.gen_range(0, n_vars)), rng.gen()))
+                .collect()
+        })
+        .collect()
+}
+
+fn main() {
+    let heuristics = [
+        ("lrb", BranchingHeuristic::Lrb),
+        (
+            "vsids",
+            BranchingHeuristic::Vsids {
+                var_inc: 1.0,
+                var_decay: 0.95,
+            },
+        ),
+    ];
+    for &(name, bh) in heuristics.iter() {
+        for &ratio in [0.0, 2.0].iter() {
+            for &n_vars in [1_000, 10_000, 100_000].iter() {
+                let mut rng = StdRng::seed_from_u64(n_vars as u64);
+                let clauses = random_3sat(n_vars, ratio, &mut rng);
+
+                let options = SolverOptions {
+                    branching_heuristic: bh,
+                    ..Default::default()
+                };
+                let mut solver = Solver::new(options);
+                solver.new_vars(n_vars);
+                for cl in clauses {
+                    solver.add_clause(cl);
+                }
+
+                let start = Instant::now();
+                let solution = solver.solve(vec![]);
+                let elapsed = start.elapsed();
+                assert!(matches!(solution, Solution::Sat(_)));
+                let decisions = solver.stats().decisions;
+                println!(
+                    "{:>6} ratio {:.1} {:>7} vars: {:>8} decisions in {:>10.3?} ({:.3?} per decision)",
+                    name,
+                    ratio,
+                    n_vars,
+                    decisions,
+                    elapsed,
+                    elapsed / decisions.max(1) as u32
+                );
+            }
+        }
+    }
+}
@@ -1,86 +1,242 @@
-use super::solver_options::ClauseDbOptions;
+use super::solver_options::{ClauseDbOptions, ReductionPolicy};
+use super::watches::Watches;
 use super::{DratClauses, VarManager};
-use solhop_types::Clause;
-use std::collections::HashMap;
+use solhop_types::{Lit, Var};
 
-#[derive(Clone, Copy, PartialEq, Debug)]
-pub enum ClauseIndex {
-    Orig(usize),
-    Lrnt(usize),
+/// Reference to a clause, i.e. the offset of its header in the clause arena.
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub struct ClauseRef(u32);
+
+// Layout of a clause in the arena:
+// [size, flags | lbd << LBD_SHIFT, activity (f32 bits), id (low bits), id (high bits), lits...]
+const HEADER_LEN: usize = 5;
+const LEARNT: u32 = 1;
+const DELETED: u32 = 1 << 1;
+const USED: u32 = 1 << 2;
+/// Set during garbage collection, the activity word then holds the new reference.
+const RELOCATED: u32 = 1 << 3;
+/// The clause was vivified and did not change since.
+const VIVIFIED: u32 = 1 << 4;
+const LBD_SHIFT: u32 = 5;
+
+fn to_word(lit: Lit) -> u32 {
+    lit.index() as u32
+}
+
+fn to_lit(word: u32) -> Lit {
+    Lit::new(Var::new((word >> 1) as usize), word & 1 == 1)
 }
 
 pub struct ClauseDb {
-    original: Vec<Clause>,
-    learnts: HashMap<usize, (Clause, f64)>,
-    curr_learnt_id: usize,
-    cla_inc: f64,
-    cla_decay: f64,
+    arena: Vec<u32>,
+    /// Number of arena words used by deleted clauses
+    wasted: usize,
+    original: Vec<ClauseRef>,
+    learnts: Vec<ClauseRef>,
+    n_original: usize,
+    n_learnts: usize,
+    cla_inc: f32,
+    cla_decay: f32,
+    reduction_policy: ReductionPolicy,
+    /// Number of learnts that are never removed by reduce_db
+    n_core: usize,
+    /// Last clause id given by `new_id`
+    last_id: u64,
 }
 
 impl ClauseDb {
     pub fn new(options: ClauseDbOptions) -> Self {
         ClauseDb {
+            arena: vec![],
+            wasted: 0,
             original: vec![],
-            learnts: HashMap::new(),
-            curr_learnt_id: 0,
-            cla_inc: options.cla_inc,
-            cla_decay: 1.0 / options.cla_decay,
+            learnts: vec![],
+            n_original: 0,
+            n_learnts: 0,
+            cla_inc: options.cla_inc as f32,
+            cla_decay: 1.0 / options.cla_decay as f32,
+            reduction_policy: options.reduction_policy,
+            n_core: 0,
+            last_id: 0,
+        }
+    }
+
+    fn is_core(&self, lbd: u32) -> bool {
+        match self.reduction_policy {
+            ReductionPolicy::Activity => false,
+            ReductionPolicy::Tiered { core_lbd, .. } => lbd <= core_lbd,
+        }
+    }
+
+    /// Lowest LBD of the learnts that can be removed by reduce_db.
+    pub fn reducible_lbd(&self) -> u32 {
+        match self.reduction_policy {
+            ReductionPolicy::Activity => 2,
+            ReductionPolicy::Tiered { core_lbd, .. } => core_lbd + 1,
         }
     }
 
     pub fn original_len(&self) -> usize {
-        self.original.len()
+        self.n_original
     }
 
     pub fn learnts_len(&self) -> usize {
-        self.learnts.len()
+        self.n_learnts
     }
 
-    pub fn add_original(&mut self, cl: Clause) -> ClauseIndex {
-        let ci = ClauseIndex::Orig(self.original.len());
-        self.original.push(cl);
-        ci
+    /// Number of learnts that can be removed by reduce_db.
+    pub fn reducible_learnts_len(&self) -> usize {
+        self.n_learnts - self.n_core
     }
 
-    pub fn add_learnt(&mut self, cl: Clause) -> ClauseIndex {
-        self.learnts.insert(self.curr_learnt_id, (cl, 0.0));
-        let ci = ClauseIndex::Lrnt(self.curr_learnt_id);
-        self.curr_learnt_id += 1;
-        self.found_clause_as_reason(ci);
-        ci
+    /// Returns a fresh clause id. Ids are given in the order in which
+    /// clauses are added or derived, as in LRAT proofs.
+    pub fn new_id(&mut self) -> u64 {
+        self.last_id += 1;
+        self.last_id
     }
 
-    pub fn get_original_mut(&mut self, index: usize) -> Option<&mut Clause> {
-        self.original.get_mut(index)
+    fn alloc(&mut self, lits: &[Lit], flags: u32, id: u64) -> ClauseRef {
+        let cr = ClauseRef(self.arena.len() as u32);
+        self.arena.push(lits.len() as u32);
+        self.arena.push(flags);
+        self.arena.push(0.0f32.to_bits());
+        self.arena.push(id as u32);
+        self.arena.push((id >> 32) as u32);
+        self.arena.extend(lits.iter().map(|&lit| to_word(lit)));
+        cr
     }
 
-    pub fn get_learnt_mut(&mut self, index: usize) -> Option<&mut Clause> {
-        self.learnts.get_mut(&index).map(|(c, _)| c)
+    pub fn add_original(&mut self, lits: &[Lit], id: u64) -> ClauseRef {
+        let cr = self.alloc(lits, 0, id);
+        self.original.push(cr);
+        self.n_original += 1;
+        cr
     }
 
-    pub fn get_clause_ref(&self, ci: ClauseIndex) -> &Clause {
-        match ci {
-            ClauseIndex::Orig(ci) => &self.original[ci],
-            ClauseIndex::Lrnt(ci) => &self.learnts.get(&ci).map(|(c, _)| c).unwrap(),
+    pub fn add_learnt(&mut self, lits: &[Lit], lbd: u32, id: u64) -> ClauseRef {
+        if self.is_core(lbd) {
+            self.n_core += 1;
         }
+        let cr = self.alloc(lits, LEARNT | USED | (lbd << LBD_SHIFT), id);
+        self.learnts.push(cr);
+        self.n_learnts += 1;
+        self.found_clause_as_reason(cr);
+        cr
+    }
+
+    fn flags(&self, cr: ClauseRef) -> u32 {
+        self.arena[cr.0 as usize + 1]
     }
 
-    pub fn get_clause_mut_ref(&mut self, ci: ClauseIndex) -> &mut Clause {
-        match ci {
-            ClauseIndex::Orig(ci) => &mut self.original[ci],
-            ClauseIndex::Lrnt(ci) => self.learnts.get_mut(&ci).map(|(c, _)| c).unwrap(),
+    fn flags_mut(&mut self, cr: ClauseRef) -> &mut u32 {
+        &mut self.arena[cr.0 as usize + 1]
+    }
+
+    pub fn size(&self, cr: ClauseRef) -> usize {
+        self.arena[cr.0 as usize] as usize
+    }
+
+    pub fn is_learnt(&self, cr: ClauseRef) -> bool {
+        self.flags(cr) & LEARNT != 0
+    }
+
+    pub fn is_deleted(&self, cr: ClauseRef) -> bool {
+        self.flags(cr) & DELETED != 0
+    }
+
+    pub fn is_vivified(&self, cr: ClauseRef) -> bool {
+        self.flags(cr) & VIVIFIED != 0
+    }
+
+    pub fn set_vivified(&mut self, cr: ClauseRef) {
+        *self.flags_mut(cr) |= VIVIFIED;
+    }
+
+    pub fn id(&self, cr: ClauseRef) -> u64 {
+        let start = cr.0 as usize;
+        self.arena[start + 3] as u64 | (self.arena[start + 4] as u64) << 32
+    }
+
+    /// Give a new id to a clause whose literals were changed.
+    pub fn set_id(&mut self, cr: ClauseRef, id: u64) {
+        let start = cr.0 as usize;
+        self.arena[start + 3] = id as u32;
+        self.arena[start + 4] = (id >> 32) as u32;
+    }
+
+    fn activity(&self, cr: ClauseRef) -> f32 {
+        f32::from_bits(self.arena[cr.0 as usize + 2])
+    }
+
+    fn set_activity(&mut self, cr: ClauseRef, activity: f32) {
+        self.arena[cr.0 as usize + 2] = activity.to_bits();
+    }
+
+    pub fn lit(&self, cr: ClauseRef, i: usize) -> Lit {
+        to_lit(self.arena[cr.0 as usize + HEADER_LEN + i])
+    }
+
+    pub fn set_lit(&mut self, cr: ClauseRef, i: usize, lit: Lit) {
+        self.arena[cr.0 as usize + HEADER_LEN + i] = to_word(lit);
+    }
+
+    pub fn swap_lits(&mut self, cr: ClauseRef, i: usize, j: usize) {
+        let start = cr.0 as usize + HEADER_LEN;
+        self.arena.swap(start + i, start + j);
+    }
+
+    pub fn lits(&self, cr: ClauseRef) -> impl ExactSizeIterator<Item = Lit> + Clone + '_ {
+        let start = cr.0 as usize + HEADER_LEN;
+        self.arena[start..start + self.size(cr)]
+            .iter()
+            .map(|&w| to_lit(w))
+    }
+
+    /// Keep only the first `size` literals of the clause,
+    /// which can then be vivified again.
+    pub fn shrink(&mut self, cr: ClauseRef, size: usize) {
+        let old_size = self.size(cr);
+        debug_assert!(size <= old_size);
+        self.arena[cr.0 as usize] = size as u32;
+        *self.flags_mut(cr) &= !VIVIFIED;
+        self.wasted += old_size - size;
+    }
+
+    pub fn found_clause_as_reason(&mut self, cr: ClauseRef) {
+        if self.is_learnt(cr) {
+            *self.flags_mut(cr) |= USED;
+            let activity = self.activity(cr) + self.cla_inc;
+            self.set_activity(cr, activity);
+            if activity > 1e20 {
+                for i in 0..self.learnts.len() {
+                    let cr = self.learnts[i];
+                    let activity = self.activity(cr);
+                    self.set_activity(cr, activity * 1e-20);
+                }
+                self.cla_inc *= 1e-20;
+            }
         }
     }
 
-    pub fn found_clause_as_reason(&mut self, ci: ClauseIndex) {
-        if let ClauseIndex::Lrnt(index) = ci {
-            let cl = self.learnts.get_mut(&index).unwrap();
-            cl.1 += self.cla_inc;
-            if cl.1 > 1e100 {
-                for (_, cl) in self.learnts.iter_mut() {
-                    cl.1 *= 1e-100;
+    /// Returns the LBD of a learnt clause.
+    pub fn lbd(&self, cr: ClauseRef) -> Option<u32> {
+        if self.is_learnt(cr) {
+            Some(self.flags(cr) >> LBD_SHIFT)
+        } else {
+            None
+        }
+    }
+
+    /// Lower the LBD of a learnt clause, recomputed while it took part in a conflict.
+    pub fn update_lbd(&mut self, cr: ClauseRef, lbd: u32) {
+        if let Some(old_lbd) = self.lbd(cr) {
+            if lbd < old_lbd {
+                if self.is_core(lbd) && !self.is_core(old_lbd) {
+                    self.n_core += 1;
                 }
-                self.cla_inc *= 1e-100;
+                let flags = self.flags_mut(cr);
+                *flags = (*flags & ((1 << LBD_SHIFT) - 1)) | (lbd << LBD_SHIFT);
             }
         }
     }
@@ -90,78 +246,202 @@ impl ClauseDb {
     }
 
     /// If the clause is reason for some variable
-    /// (INVARIANT: if it is, then it should be var corresponding to first literal),
+    /// (INVARIANT: if it is, then it should be var corresponding to first literal,
+    /// or one of the two literals of a binary clause),
     /// then the clause is locked.
-    fn is_clause_locked(&self, ci: ClauseIndex, var_manager: &VarManager) -> bool {
-        let cl = self.get_clause_ref(ci);
-        var_manager.get_reason(cl.lits[0].var()) == Some(ci)
+    pub fn is_clause_locked(&self, cr: ClauseRef, var_manager: &VarManager) -> bool {
+        var_manager.get_reason(self.lit(cr, 0).var()) == Some(cr)
+            || (self.size(cr) == 2 && var_manager.get_reason(self.lit(cr, 1).var()) == Some(cr))
     }
 
     pub(crate) fn reduce_db(
         &mut self,
         var_manager: &VarManager,
-        watches: &mut Vec<Vec<ClauseIndex>>,
+        watches: &mut Watches,
+        drat_clauses: &mut DratClauses,
+    ) {
+        match self.reduction_policy {
+            ReductionPolicy::Activity => {
+                self.reduce_db_activity(var_manager, watches, drat_clauses)
+            }
+            ReductionPolicy::Tiered {
+                core_lbd,
+                tier2_lbd,
+            } => self.reduce_db_tiered(core_lbd, tier2_lbd, var_manager, watches, drat_clauses),
+        }
+        self.purge_learnts();
+    }
+
+    fn reduce_db_activity(
+        &mut self,
+        var_manager: &VarManager,
+        watches: &mut Watches,
         drat_clauses: &mut DratClauses,
     ) {
         let mut i = 0;
-        let lim = self.cla_inc / self.learnts.len() as f64;
+        let lim = self.cla_inc / self.n_learnts as f32;
 
-        let mut acts: Vec<(usize, f64, usize)> = self
+        let mut acts: Vec<(ClauseRef, f32)> = self
             .learnts
             .iter()
-            .map(|(&i, (cl, a))| (i, *a, cl.lits.len()))
+            .map(|&cr| (cr, self.activity(cr)))
             .collect();
-        // Using clause length does help (TODO)
-        // acts.sort_by(|(_, a1, l1), (_, a2, l2)| match l2.cmp(l1) {
-        //     std::cmp::Ordering::Less => std::cmp::Ordering::Less,
-        //     std::cmp::Ordering::Equal => a1.partial_cmp(a2).unwrap(),
-        //     std::cmp::Ordering::Greater => std::cmp::Ordering::Greater,
-        // });
-        acts.sort_by(|(_, a1, _), (_, a2, _)| a1.partial_cmp(a2).unwrap());
+        acts.sort_by(|(_, a1), (_, a2)| a1.partial_cmp(a2).unwrap());
 
         while i < acts.len() / 2 {
-            let index = acts[i].0;
-            let ci = ClauseIndex::Lrnt(index);
-            if !self.is_clause_locked(ci, var_manager) {
-                self.remove_learnt(index, watches, drat_clauses);
+            let cr = acts[i].0;
+            if !self.is_clause_locked(cr, var_manager) {
+                self.remove(cr, watches, drat_clauses);
             }
             i += 1;
         }
 
-        while i < self.learnts.len() {
-            let index = acts[i].0;
-            let ci = ClauseIndex::Lrnt(index);
-            if !self.is_clause_locked(ci, var_manager) && acts[i].1 < lim {
-                self.remove_learnt(index, watches, drat_clauses);
+        while i < acts.len() {
+            let cr = acts[i].0;
+            if !self.is_clause_locked(cr, var_manager) && acts[i].1 < lim {
+                self.remove(cr, watches, drat_clauses);
             }
             i += 1;
         }
     }
 
-    pub(crate) fn remove_learnt(
+    fn reduce_db_tiered(
         &mut self,
-        index: usize,
-        watches: &mut Vec<Vec<ClauseIndex>>,
+        core_lbd: u32,
+        tier2_lbd: u32,
+        var_manager: &VarManager,
+        watches: &mut Watches,
         drat_clauses: &mut DratClauses,
     ) {
-        let learnt = self.learnts.get(&index).map(|(c, _)| c).unwrap();
-        if let Some(i) = watches[(!learnt.lits[0]).index()]
-            .iter()
-            .position(|&s| s == ClauseIndex::Lrnt(index))
-        {
-            watches[(!learnt.lits[0]).index()].remove(i);
+        // Local clauses, and tier2 clauses not used since the last reduction
+        let mut locals: Vec<(ClauseRef, u32, f32)> = vec![];
+        for i in 0..self.learnts.len() {
+            let cr = self.learnts[i];
+            let flags = self.flags(cr);
+            let lbd = flags >> LBD_SHIFT;
+            *self.flags_mut(cr) &= !USED;
+            if lbd <= core_lbd || (lbd <= tier2_lbd && flags & USED != 0) {
+                continue;
+            }
+            locals.push((cr, lbd, self.activity(cr)));
+        }
+
+        // Worst clauses first: high LBD, then low activity
+        locals.sort_by(|(_, l1, a1), (_, l2, a2)| l2.cmp(l1).then(a1.partial_cmp(a2).unwrap()));
+
+        for &(cr, _, _) in locals.iter().take(locals.len() / 2) {
+            if !self.is_clause_locked(cr, var_manager) {
+                self.remove(cr, watches, drat_clauses);
+            }
+        }
+    }
+
+    /// Mark the clause as deleted. Binary clauses are detached from the
+    /// watches right away, long clauses lazily: their watchers are dropped
+    /// by propagation or by the next garbage collection. The clause stays
+    /// in the arena until the next garbage collection.
+    pub(crate) fn remove(
+        &mut self,
+        cr: ClauseRef,
+        watches: &mut Watches,
+        drat_clauses: &mut DratClauses,
+    ) {
+        if self.size(cr) == 2 {
+            watches.detach(cr, self.lit(cr, 0), self.lit(cr, 1), true);
+        }
+        let lits: Vec<Lit> = self.lits(cr).collect();
+        drat_clauses.delete(self.id(cr), &lits);
+
+        if let Some(lbd) = self.lbd(cr) {
+            if self.is_core(lbd) {
+                self.n_core -= 1;
+            }
+            self.n_learnts -= 1;
+        } else {
+            self.n_original -= 1;
         }
-        if let Some(i) = watches[(!learnt.lits[1]).index()]
+        *self.flags_mut(cr) |= DELETED;
+        self.wasted += HEADER_LEN + self.size(cr);
+    }
+
+    /// References of the original clauses which are not removed.
+    pub fn original_refs(&self) -> Vec<ClauseRef> {
+        self.original
             .iter()
-            .position(|&s| s == ClauseIndex::Lrnt(index))
-        {
-            watches[(!learnt.lits[1]).index()].remove(i);
+            .copied()
+            .filter(|&cr| self.flags(cr) & DELETED == 0)
+            .collect()
+    }
+
+    pub fn learnt_refs(&self) -> Vec<ClauseRef> {
+        self.learnts.clone()
+    }
+
+    /// Drop deleted clauses from the learnt list after removing learnts
+    /// outside of reduce_db.
+    pub fn purge_learnts(&mut self) {
+        let arena = &self.arena;
+        self.learnts
+            .retain(|&cr| arena[cr.0 as usize + 1] & DELETED == 0);
+    }
+
+    /// Compact the arena if enough of it is used by deleted clauses,
+    /// and update the references held by the watches and the reasons.
+    pub(crate) fn garbage_collect(&mut self, watches: &mut Watches, var_manager: &mut VarManager) {
+        if self.wasted * 5 <= self.arena.len() {
+            return;
+        }
+
+        let mut arena = Vec::with_capacity(self.arena.len() - self.wasted);
+        let mut relocate = |old: &mut Vec<u32>, cr: ClauseRef| {
+            let start = cr.0 as usize;
+            let end = start + HEADER_LEN + old[start] as usize;
+            let new_cr = arena.len() as u32;
+            arena.extend_from_slice(&old[start..end]);
+            old[start + 1] |= RELOCATED;
+            old[start + 2] = new_cr;
+        };
+
+        let mut old = std::mem::take(&mut self.arena);
+        for cr in self.original.iter().chain(self.learnts.iter()) {
+            if old[cr.0 as usize + 1] & DELETED == 0 {
+                relocate(&mut old, *cr);
+            }
         }
-        drat_clauses.capture(&learnt.lits, true);
-        self.learnts.remove(&index);
+
+        let forward = |cr: ClauseRef| {
+            let start = cr.0 as usize;
+            if old[start + 1] & RELOCATED != 0 {
+                Some(ClauseRef(old[start + 2]))
+            } else {
+                None
+            }
+        };
+        watches.relocate(forward);
+        var_manager.relocate_reasons(forward);
+        self.original = self.original.iter().filter_map(|&cr| forward(cr)).collect();
+        self.learnts = self.learnts.iter().filter_map(|&cr| forward(cr)).collect();
+
+        self.arena = arena;
+        self.wasted = 0;
     }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::cdcl::SolverOptions;
 
-    pub fn learnt_indices(&self) -> Vec<usize> {
-        self.learnts.iter().map(|(&i, _)| i).collect()
+    #[test]
+    fn shrunk_clauses_can_be_vivified_again() {
+        let mut db = ClauseDb::new(SolverOptions::default().clause_db_options);
+        let lits: Vec<Lit> = (0..4).map(|v| Var::new(v).pos_lit()).collect();
+        let id = db.new_id();
+        let cr = db.add_original(&lits, id);
+        db.set_vivified(cr);
+        assert!(db.is_vivified(cr));
+        db.shrink(cr, 3);
+        assert!(!db.is_vivified(cr));
+        assert_eq!(db.lits(cr).collect::<Vec<_>>(), lits[..3].to_vec());
     }
 }
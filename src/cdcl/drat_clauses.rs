@@ -1,4 +1,5 @@
 use solhop_types::Lit;
+use std::io::{self, Write};
 
 /// Drat Clause type
 pub enum DratClause {
@@ -8,21 +9,69 @@ pub enum DratClause {
     Delete(Vec<Lit>),
 }
 
+/// Format of a streamed proof
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum ProofFormat {
+    /// Textual DRAT
+    Drat,
+    /// Binary DRAT
+    BinaryDrat,
+    /// Textual LRAT. Original clauses are numbered from 1 in the order in
+    /// which they were given to `Solver::add_clause`, so the proof matches
+    /// the formula only if all clauses are added before solving.
+    Lrat,
+}
+
+/// Destination of a proof written while solving
+pub struct ProofOutput {
+    /// Writer receiving the proof steps
+    pub writer: Box<dyn Write + Send>,
+    /// Format of the proof
+    pub format: ProofFormat,
+}
+
 /// Storage for drat clauses
 pub(crate) struct DratClauses {
     drat_clauses: Vec<DratClause>,
     capture_drat: bool,
+    output: Option<ProofOutput>,
+    /// First error returned by the proof writer
+    error: Option<io::Error>,
+    /// Id of the last added clause, used as step id of lrat deletions
+    last_id: u64,
 }
 
 impl DratClauses {
-    pub fn new(capture_drat: bool) -> Self {
+    pub fn new(capture_drat: bool, output: Option<ProofOutput>) -> Self {
         Self {
             drat_clauses: vec![],
             capture_drat,
+            output,
+            error: None,
+            last_id: 0,
         }
     }
 
-    pub fn capture(&mut self, lits: &[Lit], is_delete: bool) {
+    /// Whether the proof needs clause ids and antecedents of added clauses.
+    pub fn needs_hints(&self) -> bool {
+        match &self.output {
+            Some(output) => output.format == ProofFormat::Lrat,
+            None => false,
+        }
+    }
+
+    /// Add a clause with the given id, derived from the clauses `hints`
+    /// by unit propagation, in that order.
+    pub fn add(&mut self, id: u64, lits: &[Lit], hints: &[u64]) {
+        self.last_id = self.last_id.max(id);
+        self.capture(id, lits, hints, false);
+    }
+
+    pub fn delete(&mut self, id: u64, lits: &[Lit]) {
+        self.capture(id, lits, &[], true);
+    }
+
+    fn capture(&mut self, id: u64, lits: &[Lit], hints: &[u64], is_delete: bool) {
         if self.capture_drat {
             self.drat_clauses.push(if is_delete {
                 DratClause::Delete(Vec::from(lits))
@@ -30,6 +79,30 @@ impl DratClauses {
                 DratClause::Add(Vec::from(lits))
             });
         }
+        if let Some(output) = &mut self.output {
+            if self.error.is_none() {
+                let res = match output.format {
+                    ProofFormat::Drat => write_text(&mut output.writer, lits, is_delete),
+                    ProofFormat::BinaryDrat => write_binary(&mut output.writer, lits, is_delete),
+                    ProofFormat::Lrat if is_delete => {
+                        write_lrat_delete(&mut output.writer, self.last_id, id)
+                    }
+                    ProofFormat::Lrat => write_lrat_add(&mut output.writer, id, lits, hints),
+                };
+                self.error = res.err();
+            }
+        }
+    }
+
+    /// Flush the proof writer, reporting the first error met while writing.
+    pub fn flush(&mut self) -> io::Result<()> {
+        if let Some(e) = self.error.take() {
+            return Err(e);
+        }
+        match &mut self.output {
+            Some(output) => output.writer.flush(),
+            None => Ok(()),
+        }
     }
 
     pub fn drat_clauses(self) -> Option<Vec<DratClause>> {
@@ -40,3 +113,64 @@ impl DratClauses {
         }
     }
 }
+
+/// Dimacs representation of a literal
+fn dimacs(lit: Lit) -> i64 {
+    let v = lit.var().index() as i64 + 1;
+    if lit.sign() {
+        -v
+    } else {
+        v
+    }
+}
+
+fn write_text<W: Write + ?Sized>(writer: &mut W, lits: &[Lit], is_delete: bool) -> io::Result<()> {
+    if is_delete {
+        write!(writer, "d ")?;
+    }
+    for &lit in lits {
+        write!(writer, "{} ", dimacs(lit))?;
+    }
+    writeln!(writer, "0")
+}
+
+fn write_lrat_add<W: Write + ?Sized>(
+    writer: &mut W,
+    id: u64,
+    lits: &[Lit],
+    hints: &[u64],
+) -> io::Result<()> {
+    write!(writer, "{} ", id)?;
+    for &lit in lits {
+        write!(writer, "{} ", dimacs(lit))?;
+    }
+    write!(writer, "0 ")?;
+    for &hint in hints {
+        write!(writer, "{} ", hint)?;
+    }
+    writeln!(writer, "0")
+}
+
+fn write_lrat_delete<W: Write + ?Sized>(writer: &mut W, step: u64, id: u64) -> io::Result<()> {
+    writeln!(writer, "{} d {} 0", step, id)
+}
+
+/// Binary DRAT: `a` or `d` followed by the literals, each mapped to
+/// `2 * var + sign` (1-based var) in variable-length 7-bit encoding, and `0`.
+fn write_binary<W: Write + ?Sized>(
+    writer: &mut W,
+    lits: &[Lit],
+    is_delete: bool,
+) -> io::Result<()> {
+    let mut buf = vec![if is_delete { b'd' } else { b'a' }];
+    for &lit in lits {
+        let mut u = 2 * (lit.var().index() as u64 + 1) + lit.sign() as u64;
+        while u > 127 {
+            buf.push((u & 127) as u8 | 128);
+            u >>= 7;
+        }
+        buf.push(u as u8);
+    }
+    buf.push(0);
+    writer.write_all(&buf)
+}
@@ -0,0 +1,452 @@
+use super::reconstruction::Reconstruction;
+use super::solver_options::PreprocessOptions;
+use super::DratClauses;
+use solhop_types::{Lit, Var};
+
+/// Number of passes over the variables trying to eliminate them.
+const ELIM_ROUNDS: usize = 3;
+
+/// Outcome of `eliminate`.
+pub struct Elimination {
+    /// Indices of the input clauses that were removed
+    pub removed: Vec<usize>,
+    /// Derived clauses to add instead
+    pub added: Vec<Vec<Lit>>,
+    pub eliminated: Vec<Var>,
+    /// The empty clause was derived
+    pub unsat: bool,
+    pub subsumed: u64,
+    pub strengthened: u64,
+    pub blocked: u64,
+    pub covered: u64,
+}
+
+/// SatELite-style preprocessing of a clause set: backward subsumption,
+/// self-subsuming resolution, bounded variable elimination, and blocked
+/// and covered clause elimination.
+struct Eliminator<'a> {
+    /// Clauses with literals sorted by index, the input ones first
+    clauses: Vec<Vec<Lit>>,
+    removed: Vec<bool>,
+    n_input: usize,
+    /// Clauses containing each literal, removed clauses being dropped lazily
+    occurs: Vec<Vec<usize>>,
+    /// Literals of the clause being checked for subsumption or resolved
+    marks: Vec<bool>,
+    /// Clauses to check for backward subsumption
+    queue: Vec<usize>,
+    queued: Vec<bool>,
+    /// Variables that may be eliminated
+    candidates: &'a [bool],
+    options: &'a PreprocessOptions,
+    /// Number of literals visited
+    steps: u64,
+    drat_clauses: &'a mut DratClauses,
+    reconstruction: &'a mut Reconstruction,
+    eliminated: Vec<Var>,
+    unsat: bool,
+    subsumed: u64,
+    strengthened: u64,
+    blocked: u64,
+    covered: u64,
+}
+
+/// Eliminate variables among the candidates from the clauses, which must
+/// not contain literals assigned at level 0, then the clauses blocked or
+/// covered on literals of the candidates. Derived clauses are added to the
+/// proof and the removed clauses that are not implied by the remaining ones
+/// are pushed on the reconstruction stack.
+pub fn eliminate(
+    clauses: Vec<Vec<Lit>>,
+    candidates: &[bool],
+    options: &PreprocessOptions,
+    drat_clauses: &mut DratClauses,
+    reconstruction: &mut Reconstruction,
+) -> Elimination {
+    let n_vars = candidates.len();
+    let mut elim = Eliminator {
+        clauses: vec![],
+        removed: vec![],
+        n_input: clauses.len(),
+        occurs: vec![vec![]; 2 * n_vars],
+        marks: vec![false; 2 * n_vars],
+        queue: vec![],
+        queued: vec![],
+        candidates,
+        options,
+        steps: 0,
+        drat_clauses,
+        reconstruction,
+        eliminated: vec![],
+        unsat: false,
+        subsumed: 0,
+        strengthened: 0,
+        blocked: 0,
+        covered: 0,
+    };
+    for mut lits in clauses {
+        lits.sort_by_key(|lit| lit.index());
+        elim.push_clause(lits);
+    }
+    elim.run();
+
+    let n_input = elim.n_input;
+    let removed = (0..n_input).filter(|&c| elim.removed[c]).collect();
+    let added = elim
+        .clauses
+        .drain(n_input..)
+        .zip(elim.removed[n_input..].iter())
+        .filter(|(_, &removed)| !removed)
+        .map(|(lits, _)| lits)
+        .collect();
+    Elimination {
+        removed,
+        added,
+        eliminated: elim.eliminated,
+        unsat: elim.unsat,
+        subsumed: elim.subsumed,
+        strengthened: elim.strengthened,
+        blocked: elim.blocked,
+        covered: elim.covered,
+    }
+}
+
+impl<'a> Eliminator<'a> {
+    fn out_of_effort(&self) -> bool {
+        self.steps > self.options.effort
+    }
+
+    fn push_clause(&mut self, lits: Vec<Lit>) -> usize {
+        let c = self.clauses.len();
+        for lit in lits.iter() {
+            self.occurs[lit.index()].push(c);
+        }
+        self.clauses.push(lits);
+        self.removed.push(false);
+        self.queued.push(true);
+        self.queue.push(c);
+        c
+    }
+
+    /// Add a clause derived from the others.
+    fn add_derived(&mut self, lits: Vec<Lit>) {
+        self.drat_clauses.add(0, &lits, &[]);
+        if lits.is_empty() {
+            self.unsat = true;
+        }
+        self.push_clause(lits);
+    }
+
+    fn remove_clause(&mut self, c: usize) {
+        self.removed[c] = true;
+        if c >= self.n_input {
+            self.drat_clauses.delete(0, &self.clauses[c]);
+        }
+    }
+
+    /// Clauses containing the literal.
+    fn occurrences(&mut self, lit: Lit) -> Vec<usize> {
+        let removed = &self.removed;
+        self.occurs[lit.index()].retain(|&c| !removed[c]);
+        self.occurs[lit.index()].clone()
+    }
+
+    fn n_occurrences(&self, var: Var) -> usize {
+        self.occurs[var.pos_lit().index()].len() + self.occurs[var.neg_lit().index()].len()
+    }
+
+    fn run(&mut self) {
+        self.subsume_queue();
+        if self.options.elimination {
+            self.eliminate_vars();
+        }
+        if self.options.blocked_clauses || self.options.covered_clauses {
+            self.eliminate_clauses();
+        }
+    }
+
+    fn eliminate_vars(&mut self) {
+        for _ in 0..ELIM_ROUNDS {
+            let mut vars: Vec<Var> = (0..self.candidates.len())
+                .map(Var::new)
+                .filter(|v| self.candidates[v.index()])
+                .collect();
+            vars.sort_by_key(|&v| self.n_occurrences(v));
+
+            let mut progress = false;
+            for v in vars {
+                if self.unsat || self.out_of_effort() {
+                    return;
+                }
+                if self.try_eliminate(v) {
+                    progress = true;
+                    self.subsume_queue();
+                }
+            }
+            if !progress {
+                break;
+            }
+        }
+    }
+
+    /// Check the queued clauses for backward subsumption.
+    fn subsume_queue(&mut self) {
+        while let Some(c) = self.queue.pop() {
+            self.queued[c] = false;
+            if self.unsat || self.out_of_effort() {
+                continue;
+            }
+            if !self.removed[c] {
+                self.backward_subsume(c);
+            }
+        }
+    }
+
+    /// Remove the clauses subsumed by the clause `c`, and strengthen those
+    /// which contain all its literals but one, negated.
+    fn backward_subsume(&mut self, c: usize) {
+        let lits = self.clauses[c].clone();
+        let best = match lits.iter().min_by_key(|lit| self.n_occurrences(lit.var())) {
+            Some(&lit) => lit,
+            None => return,
+        };
+        if self.n_occurrences(best.var()) > self.options.subsumption_limit {
+            return;
+        }
+
+        for lit in lits.iter() {
+            self.marks[lit.index()] = true;
+        }
+        let mut others = self.occurrences(best);
+        others.extend(self.occurrences(!best));
+        for d in others {
+            if d == c || self.removed[d] || self.clauses[d].len() < lits.len() {
+                continue;
+            }
+            self.steps += self.clauses[d].len() as u64;
+            let mut hits = 0;
+            let mut negated = None;
+            for &lit in self.clauses[d].iter() {
+                if self.marks[lit.index()] {
+                    hits += 1;
+                } else if self.marks[(!lit).index()] {
+                    if negated.is_some() {
+                        negated = None;
+                        hits = 0;
+                        break;
+                    }
+                    negated = Some(lit);
+                }
+            }
+            match negated {
+                None if hits == lits.len() => {
+                    self.remove_clause(d);
+                    self.subsumed += 1;
+                }
+                Some(lit) if hits + 1 == lits.len() => {
+                    let strengthened: Vec<Lit> = self.clauses[d]
+                        .iter()
+                        .copied()
+                        .filter(|&l| l != lit)
+                        .collect();
+                    self.add_derived(strengthened);
+                    self.remove_clause(d);
+                    self.strengthened += 1;
+                }
+                _ => {}
+            }
+        }
+        for lit in lits.iter() {
+            self.marks[lit.index()] = false;
+        }
+    }
+
+    /// Resolvent of the clauses on the variable of `pivot`, which is in `c`,
+    /// `None` if it is a tautology.
+    fn resolve(&mut self, c: usize, d: usize, pivot: Lit) -> Option<Vec<Lit>> {
+        self.steps += (self.clauses[c].len() + self.clauses[d].len()) as u64;
+        let mut resolvent: Vec<Lit> = vec![];
+        for &lit in self.clauses[c].iter() {
+            if lit != pivot {
+                self.marks[lit.index()] = true;
+                resolvent.push(lit);
+            }
+        }
+        let mut tautology = false;
+        for &lit in self.clauses[d].iter() {
+            if lit == !pivot || self.marks[lit.index()] {
+                continue;
+            }
+            if self.marks[(!lit).index()] {
+                tautology = true;
+                break;
+            }
+            resolvent.push(lit);
+        }
+        for &lit in self.clauses[c].iter() {
+            self.marks[lit.index()] = false;
+        }
+        if tautology {
+            None
+        } else {
+            resolvent.sort_by_key(|lit| lit.index());
+            Some(resolvent)
+        }
+    }
+
+    /// Eliminate the variable by clause distribution if the number of
+    /// resolvents does not exceed the number of its clauses by more than
+    /// the allowed growth.
+    fn try_eliminate(&mut self, v: Var) -> bool {
+        let pos = self.occurrences(v.pos_lit());
+        let neg = self.occurrences(v.neg_lit());
+        let n_clauses = pos.len() + neg.len();
+        if n_clauses == 0 || n_clauses > self.options.elim_occurrence_limit {
+            return false;
+        }
+
+        let mut resolvents = vec![];
+        for &c in pos.iter() {
+            for &d in neg.iter() {
+                if let Some(resolvent) = self.resolve(c, d, v.pos_lit()) {
+                    if resolvent.len() > self.options.elim_resolvent_limit
+                        || resolvents.len() >= n_clauses + self.options.elim_grow
+                    {
+                        return false;
+                    }
+                    resolvents.push(resolvent);
+                }
+            }
+        }
+
+        for &c in pos.iter() {
+            self.reconstruction
+                .push(v.pos_lit(), self.clauses[c].clone());
+        }
+        for &d in neg.iter() {
+            self.reconstruction
+                .push(v.neg_lit(), self.clauses[d].clone());
+        }
+        for resolvent in resolvents {
+            self.add_derived(resolvent);
+        }
+        for c in pos.into_iter().chain(neg) {
+            self.remove_clause(c);
+        }
+        self.eliminated.push(v);
+        true
+    }
+
+    /// Remove the clauses blocked on one of their literals, or covered if
+    /// covered clause elimination is enabled.
+    fn eliminate_clauses(&mut self) {
+        for _ in 0..ELIM_ROUNDS {
+            let mut progress = false;
+            for c in 0..self.clauses.len() {
+                if self.unsat || self.out_of_effort() {
+                    return;
+                }
+                if !self.removed[c] && self.try_remove_clause(c) {
+                    progress = true;
+                }
+            }
+            if !progress {
+                break;
+            }
+        }
+    }
+
+    /// Literals of all the non tautological resolvents of the marked clause
+    /// with the clauses of `!lit`, not in the clause. `None` if all the
+    /// resolvents are tautologies, i.e. the clause is blocked on `lit`.
+    fn covered_lits(&mut self, lit: Lit) -> Option<Vec<Lit>> {
+        let mut covered: Option<Vec<Lit>> = None;
+        for d in self.occurrences(!lit) {
+            self.steps += self.clauses[d].len() as u64;
+            let tautology = self.clauses[d]
+                .iter()
+                .any(|&l| l != !lit && self.marks[(!l).index()]);
+            if tautology {
+                continue;
+            }
+            let marks = &self.marks;
+            let others = self.clauses[d]
+                .iter()
+                .copied()
+                .filter(|&l| l != !lit && !marks[l.index()]);
+            covered = Some(match covered {
+                None => others.collect(),
+                Some(covered) => {
+                    let others: Vec<Lit> = others.collect();
+                    covered.into_iter().filter(|l| others.contains(l)).collect()
+                }
+            });
+        }
+        covered
+    }
+
+    /// Remove the clause if it is blocked, or covered, on a literal of a
+    /// candidate. Covered literals are added to a copy of the clause until
+    /// it is blocked, and each extended clause is pushed on the
+    /// reconstruction stack with the literal used to extend it.
+    fn try_remove_clause(&mut self, c: usize) -> bool {
+        let mut lits = self.clauses[c].clone();
+        for lit in lits.iter() {
+            self.marks[lit.index()] = true;
+        }
+        let mut extensions = vec![];
+        let mut blocking = None;
+        'extend: for _ in 0..ELIM_ROUNDS {
+            let mut extended = false;
+            let mut i = 0;
+            while i < lits.len() {
+                let lit = lits[i];
+                i += 1;
+                if !self.candidates[lit.var().index()] {
+                    continue;
+                }
+                match self.covered_lits(lit) {
+                    None => {
+                        blocking = Some(lit);
+                        break 'extend;
+                    }
+                    Some(covered) if self.options.covered_clauses && !covered.is_empty() => {
+                        extensions.push((lit, lits.clone()));
+                        for l in covered {
+                            self.marks[l.index()] = true;
+                            lits.push(l);
+                        }
+                        extended = true;
+                    }
+                    _ => {}
+                }
+                if self.out_of_effort() {
+                    break 'extend;
+                }
+            }
+            if !extended {
+                break;
+            }
+        }
+        for lit in lits.iter() {
+            self.marks[lit.index()] = false;
+        }
+
+        match blocking {
+            Some(pivot) => {
+                if extensions.is_empty() {
+                    self.blocked += 1;
+                } else {
+                    self.covered += 1;
+                }
+                for (lit, extension) in extensions {
+                    self.reconstruction.push(lit, extension);
+                }
+                self.reconstruction.push(pivot, lits);
+                self.remove_clause(c);
+                true
+            }
+            None => false,
+        }
+    }
+}
@@ -1,12 +1,22 @@
 mod clause_db;
 mod drat_clauses;
+mod elim;
+mod reconstruction;
+mod restart;
 mod solver;
 mod solver_options;
+mod stats;
 mod trail;
+mod var_heap;
 mod var_manager;
+mod watches;
 
-pub use drat_clauses::DratClause;
 pub(crate) use drat_clauses::DratClauses;
+pub use drat_clauses::{DratClause, ProofFormat, ProofOutput};
 pub use solver::Solver;
-pub use solver_options::SolverOptions;
+pub use solver_options::{
+    BranchingHeuristic, ClauseDbOptions, InprocessOptions, LearntMinimization, Polarity,
+    PreprocessOptions, ReductionPolicy, RestartStrategy, SolverOptions,
+};
+pub use stats::SolverStats;
 pub(crate) use var_manager::VarManager;
@@ -0,0 +1,95 @@
+use solhop_types::{Lit, Var};
+use std::collections::HashSet;
+
+/// Clauses removed by preprocessing, each with the literal to make true
+/// when the clause is falsified by a model of the remaining clauses.
+pub struct Reconstruction {
+    stack: Vec<(Lit, Vec<Lit>)>,
+    /// Number of clauses of the stack with each literal as pivot
+    n_pivots: Vec<usize>,
+}
+
+impl Reconstruction {
+    pub fn new() -> Self {
+        Reconstruction {
+            stack: vec![],
+            n_pivots: vec![],
+        }
+    }
+
+    /// Push a removed clause with its pivot, which must be one of its literals.
+    pub fn push(&mut self, pivot: Lit, lits: Vec<Lit>) {
+        if self.n_pivots.len() <= pivot.index() {
+            self.n_pivots.resize(2 * (pivot.var().index() + 1), 0);
+        }
+        self.n_pivots[pivot.index()] += 1;
+        self.stack.push((pivot, lits));
+    }
+
+    /// Extend a model of the remaining clauses to a model of all the clauses,
+    /// going through the removed clauses in reverse order.
+    pub fn extend(&self, model: &mut [bool]) {
+        for (pivot, lits) in self.stack.iter().rev() {
+            let satisfied = lits
+                .iter()
+                .any(|lit| model[lit.var().index()] != lit.sign());
+            if !satisfied {
+                model[pivot.var().index()] = !pivot.sign();
+            }
+        }
+    }
+
+    fn is_pivot(&self, lit: Lit) -> bool {
+        self.n_pivots.get(lit.index()).copied().unwrap_or(0) > 0
+    }
+
+    /// Take out the removed clauses which the extension could falsify once
+    /// the literals are in the formula, i.e. those whose pivot is the
+    /// negation of one of them, and those of the literals of these clauses
+    /// in turn. Returns the clauses with their pivot first, most recently
+    /// removed first, and the variables of their literals.
+    pub fn restore(&mut self, lits: &[Lit]) -> (Vec<Vec<Lit>>, Vec<Var>) {
+        if !lits.iter().any(|&lit| self.is_pivot(!lit)) {
+            return (vec![], vec![]);
+        }
+        let mut restored = vec![false; self.stack.len()];
+        let mut queued = HashSet::new();
+        let mut queue: Vec<Lit> = vec![];
+        let mut vars = vec![];
+        for &lit in lits {
+            if queued.insert(lit.index()) {
+                queue.push(lit);
+            }
+        }
+        while let Some(lit) = queue.pop() {
+            for (i, (pivot, clause)) in self.stack.iter().enumerate() {
+                if restored[i] || *pivot != !lit {
+                    continue;
+                }
+                restored[i] = true;
+                for &l in clause {
+                    vars.push(l.var());
+                    if queued.insert(l.index()) {
+                        queue.push(l);
+                    }
+                }
+            }
+        }
+
+        let mut clauses = vec![];
+        let mut kept = vec![];
+        for (i, (pivot, mut clause)) in self.stack.drain(..).enumerate() {
+            if restored[i] {
+                self.n_pivots[pivot.index()] -= 1;
+                let pos = clause.iter().position(|&l| l == pivot).unwrap();
+                clause.swap(0, pos);
+                clauses.push(clause);
+            } else {
+                kept.push((pivot, clause));
+            }
+        }
+        self.stack = kept;
+        clauses.reverse();
+        (clauses, vars)
+    }
+}
@@ -0,0 +1,133 @@
+use super::solver_options::RestartStrategy;
+
+/// Exponential moving average, biased towards the mean of the first
+/// samples while fewer than `1 / alpha` samples were seen.
+struct Ema {
+    value: f64,
+    alpha: f64,
+    count: u64,
+}
+
+impl Ema {
+    fn new(alpha: f64) -> Self {
+        Ema {
+            value: 0.0,
+            alpha,
+            count: 0,
+        }
+    }
+
+    fn update(&mut self, x: f64) {
+        self.count += 1;
+        let alpha = self.alpha.max(1.0 / self.count as f64);
+        self.value += alpha * (x - self.value);
+    }
+}
+
+/// Decides when `Solver::search` should restart.
+pub struct Restarter {
+    strategy: RestartStrategy,
+    restarts: u32,
+    conflicts: u64,
+    total_conflicts: u64,
+    lbd_fast: Ema,
+    lbd_slow: Ema,
+    trail: Ema,
+}
+
+impl Restarter {
+    pub fn new(strategy: RestartStrategy) -> Self {
+        let (fast_alpha, slow_alpha) = match strategy {
+            RestartStrategy::Glucose {
+                fast_alpha,
+                slow_alpha,
+                ..
+            } => (fast_alpha, slow_alpha),
+            _ => (1.0, 1.0),
+        };
+        Restarter {
+            strategy,
+            restarts: 0,
+            conflicts: 0,
+            total_conflicts: 0,
+            lbd_fast: Ema::new(fast_alpha),
+            lbd_slow: Ema::new(slow_alpha),
+            trail: Ema::new(1.0 / 5000.0),
+        }
+    }
+
+    /// Record a conflict whose learnt clause has glue `lbd`,
+    /// found with `trail_len` assigned variables.
+    pub fn on_conflict(&mut self, lbd: u32, trail_len: usize) {
+        self.conflicts += 1;
+        self.total_conflicts += 1;
+        if let RestartStrategy::Glucose { block, .. } = self.strategy {
+            if let Some(block) = block {
+                // Block the restart if the solver seems to approach a model
+                if self.total_conflicts > 10000 && trail_len as f64 > block * self.trail.value {
+                    self.conflicts = 0;
+                }
+                self.trail.update(trail_len as f64);
+            }
+            self.lbd_fast.update(lbd as f64);
+            self.lbd_slow.update(lbd as f64);
+        }
+    }
+
+    pub fn should_restart(&self) -> bool {
+        match self.strategy {
+            RestartStrategy::None => false,
+            RestartStrategy::Luby { unit } => {
+                self.conflicts >= unit as u64 * luby(2.0, self.restarts) as u64
+            }
+            RestartStrategy::Geometric { first, inc } => {
+                self.conflicts as f64 >= first * inc.powi(self.restarts as i32)
+            }
+            RestartStrategy::Glucose {
+                margin,
+                min_conflicts,
+                ..
+            } => {
+                self.conflicts >= min_conflicts as u64
+                    && self.lbd_fast.value > margin * self.lbd_slow.value
+            }
+        }
+    }
+
+    /// Start the restart schedule from the beginning, for a new call to `solve`.
+    pub fn reset_schedule(&mut self) {
+        self.restarts = 0;
+        self.conflicts = 0;
+    }
+
+    pub fn on_restart(&mut self) {
+        self.restarts += 1;
+        self.conflicts = 0;
+    }
+}
+
+/// Finite subsequences of the Luby-sequence:
+///
+/// 0: 1
+/// 1: 1 1 2
+/// 2: 1 1 2 1 1 2 4
+/// 3: 1 1 2 1 1 2 4 1 1 2 1 1 2 4 8
+/// ...
+fn luby(y: f64, mut x: u32) -> f64 {
+    // Find the finite subsequence that contains index 'x', and the
+    // size of that subsequence:
+    let mut size = 1;
+    let mut seq = 0;
+    while size < x + 1 {
+        seq += 1;
+        size = 2 * size + 1;
+    }
+
+    while size - 1 != x {
+        size = (size - 1) >> 1;
+        seq -= 1;
+        x %= size;
+    }
+
+    y.powi(seq)
+}
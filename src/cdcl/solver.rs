@@ -1,38 +1,139 @@
-use super::clause_db::{ClauseDb, ClauseIndex};
+use super::clause_db::{ClauseDb, ClauseRef};
 use super::drat_clauses::{DratClause, DratClauses};
-use super::solver_options::SolverOptions;
+use super::elim;
+use super::reconstruction::Reconstruction;
+use super::restart::Restarter;
+use super::solver_options::{
+    InprocessOptions, LearntMinimization, PreprocessOptions, SolverOptions,
+};
+use super::stats::SolverStats;
 use super::trail::Trail;
+use super::watches::{Watcher, Watches};
 use super::VarManager;
+use crate::check::{check_model, InternalError};
+use crate::limits::{Limits, Usage};
 use solhop_types::{Clause, LBool, Lit, Solution, Var, UNDEF_LIT};
-use std::collections::HashSet;
 use std::collections::VecDeque;
+use std::collections::{HashMap, HashSet};
+use std::time::Instant;
 
 /// Represents a CDCL solver.
 pub struct Solver {
     undef_state: bool,
     clause_db: ClauseDb,
     var_manager: VarManager,
-    watches: Vec<Vec<ClauseIndex>>,
+    watches: Watches,
     prop_q: VecDeque<Lit>,
     trail: Trail,
-    root_level: i32,
     drat_clauses: DratClauses,
+    restarter: Restarter,
+    assumptions: Vec<Lit>,
+    /// Assumptions before this index are known to be true
+    assump_head: usize,
+    /// Failed assumptions of the last call to solve
+    conflict: Vec<Lit>,
+    learnt_minimization: LearntMinimization,
+    seen: Vec<bool>,
+    /// Id of the unit clause of each variable assigned at level 0
+    unit_ids: Vec<u64>,
+    /// Antecedents of the last learnt clause, in propagation order
+    hints: Vec<u64>,
+    /// Original clause with id found false at level 0 by `add_clause`.
+    /// The empty clause is added to the proof by the next call to `solve`.
+    falsified: Option<(Vec<Lit>, u64)>,
+    /// Copy of the clauses given to `add_clause`, in self-check mode
+    checked_clauses: Option<Vec<Clause>>,
+    stats: SolverStats,
+    preprocess: PreprocessOptions,
+    /// Clauses were added since the last preprocessing
+    preprocess_pending: bool,
+    /// Variables which must not be eliminated
+    frozen: Vec<bool>,
+    /// Clauses removed by preprocessing and substitution
+    reconstruction: Reconstruction,
+    inprocess: InprocessOptions,
+    /// Number of conflicts at which the next inprocessing step is done
+    next_inprocess: u64,
+    /// Variable from which the next probing step starts
+    probe_head: usize,
+    limits: Limits,
+    /// Time and statistics at the start of the current call to solve
+    budget_start: Usage,
+    max_learnts: f64,
+    learntsize_adjust_confl: f64,
+    learntsize_adjust_cnt: u32,
 }
 
 impl Solver {
     /// Create a new CDCL solver.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the proof is in LRAT format and a simplification whose
+    /// steps are not given LRAT hints is enabled: variable, blocked or
+    /// covered clause elimination, failed literal probing, equivalent
+    /// literal substitution or vivification.
     pub fn new(options: SolverOptions) -> Self {
+        let drat_clauses = DratClauses::new(options.capture_drat, options.proof_output);
+        if drat_clauses.needs_hints() {
+            assert!(
+                !options.preprocess.elimination,
+                "variable elimination is not supported with LRAT proofs"
+            );
+            assert!(
+                !(options.preprocess.blocked_clauses || options.preprocess.covered_clauses),
+                "blocked and covered clause elimination are not supported with LRAT proofs"
+            );
+            assert!(
+                !options.inprocess.probing,
+                "failed literal probing is not supported with LRAT proofs"
+            );
+            assert!(
+                !options.inprocess.substitution,
+                "equivalent literal substitution is not supported with LRAT proofs"
+            );
+            assert!(
+                !options.inprocess.vivification,
+                "vivification is not supported with LRAT proofs"
+            );
+        }
         let clause_db = ClauseDb::new(options.clause_db_options);
-        let var_manager = VarManager::new(options.branching_heuristic);
+        let var_manager = VarManager::new(options.branching_heuristic, options.polarity);
         Self {
             undef_state: false,
             clause_db,
             var_manager,
-            watches: vec![],
+            watches: Watches::new(),
             prop_q: VecDeque::new(),
             trail: Trail::new(),
-            root_level: 0,
-            drat_clauses: DratClauses::new(options.capture_drat),
+            drat_clauses,
+            restarter: Restarter::new(options.restart_strategy),
+            assumptions: vec![],
+            assump_head: 0,
+            conflict: vec![],
+            learnt_minimization: options.learnt_minimization,
+            seen: vec![],
+            unit_ids: vec![],
+            hints: vec![],
+            falsified: None,
+            checked_clauses: if options.self_check {
+                Some(vec![])
+            } else {
+                None
+            },
+            stats: SolverStats::default(),
+            preprocess: options.preprocess,
+            preprocess_pending: true,
+            frozen: vec![],
+            reconstruction: Reconstruction::new(),
+            inprocess: options.inprocess,
+            next_inprocess: 0,
+            probe_head: 0,
+            limits: options.limits,
+            budget_start: Usage::new(),
+            max_learnts: 0.0,
+            learntsize_adjust_confl: 0.0,
+            learntsize_adjust_cnt: 0,
         }
     }
 
@@ -56,6 +157,32 @@ impl Solver {
         self.clause_db.learnts_len()
     }
 
+    /// Returns the search statistics.
+    pub fn stats(&self) -> &SolverStats {
+        &self.stats
+    }
+
+    /// Set the resource limits of the next calls to `solve`.
+    pub fn set_limits(&mut self, limits: Limits) {
+        self.limits = limits;
+    }
+
+    /// Whether no resource limit is reached in the current call to solve.
+    fn within_budget(&mut self) -> bool {
+        let mut usage = Usage {
+            start: self.budget_start.start,
+            conflicts: self.stats.conflicts - self.budget_start.conflicts,
+            propagations: self.stats.propagations - self.budget_start.propagations,
+            flips: 0,
+            checks: self.budget_start.checks,
+            timed_out: self.budget_start.timed_out,
+        };
+        let reached = self.limits.reached(&mut usage);
+        self.budget_start.checks = usage.checks;
+        self.budget_start.timed_out = usage.timed_out;
+        !reached
+    }
+
     /// Returns the assignment of the variable.
     pub fn value(&self, x: Var) -> LBool {
         self.var_manager.value(x)
@@ -68,8 +195,10 @@ impl Solver {
 
     /// Add a new variable to the solver.
     pub fn new_var(&mut self) -> Var {
-        self.watches.push(vec![]);
-        self.watches.push(vec![]);
+        self.watches.new_var();
+        self.seen.push(false);
+        self.unit_ids.push(0);
+        self.frozen.push(false);
         self.var_manager.new_var()
     }
 
@@ -80,89 +209,188 @@ impl Solver {
 
     /// Add a new clause to the solver.
     pub fn add_clause(&mut self, lits: Vec<Lit>) {
-        let (r, _) = self.clause_new(lits, false);
+        if let Some(clauses) = &mut self.checked_clauses {
+            clauses.push(Clause { lits: lits.clone() });
+        }
+        self.restore(&lits);
+        self.preprocess_pending = true;
+        let id = self.clause_db.new_id();
+        let (r, _) = self.clause_new(lits, None, id);
         if !r {
             self.undef_state = true;
         }
     }
 
-    /// Drat clauses
-    pub fn drat_clauses(self) -> Option<Vec<DratClause>> {
-        self.drat_clauses.drat_clauses()
-    }
-
-    /// Assume p is true and simplify the clause
-    fn clause_propagate(&mut self, ci: ClauseIndex, p: Lit) -> bool {
-        let clause = match ci {
-            ClauseIndex::Orig(index) => self.clause_db.get_original_mut(index).unwrap(),
-            ClauseIndex::Lrnt(index) => self.clause_db.get_learnt_mut(index).unwrap(),
-        };
-
-        // Make sure false lit at cl.lits[1]
-        if clause.lits[0] == !p {
-            clause.lits[0] = clause.lits[1];
-            clause.lits[1] = !p;
+    /// Freeze or unfreeze a variable. Frozen variables are not eliminated
+    /// by preprocessing. Freezing an eliminated variable adds its clauses back.
+    pub fn set_frozen(&mut self, var: Var, frozen: bool) {
+        self.frozen[var.index()] = frozen;
+        if frozen {
+            self.restore(&[var.pos_lit(), var.neg_lit()]);
         }
+    }
 
-        // If 0th watch is true, clause is already satisfied
-        if self.var_manager.value_lit(clause.lits[0]) == LBool::True {
-            // Re insert clause into watcher list
-            self.watches[p.index()].push(ci);
-            return true;
+    /// Add back the clauses removed by preprocessing or substitution that the model
+    /// extension could falsify once the literals are in the formula.
+    fn restore(&mut self, lits: &[Lit]) {
+        let (clauses, vars) = self.reconstruction.restore(lits);
+        for var in vars.into_iter().chain(lits.iter().map(|lit| lit.var())) {
+            if !self.var_manager.is_decision(var) {
+                self.var_manager.set_decision(var, true);
+            }
         }
-
-        // Look for a new literal to watch
-        for i in 2..clause.lits.len() {
-            if self.var_manager.value_lit(clause.lits[i]) != LBool::False {
-                clause.lits[1] = clause.lits[i];
-                clause.lits[i] = !p;
-                self.watches[(!clause.lits[1]).index()].push(ci);
-                return true;
+        for lits in clauses {
+            let id = self.clause_db.new_id();
+            self.drat_clauses.add(id, &lits, &[]);
+            let (r, _) = self.clause_new(lits, None, id);
+            if !r {
+                self.undef_state = true;
             }
         }
+    }
+
+    /// Set the preferred phase of a variable, used with `Polarity::Preferred`.
+    /// `None` removes the preference.
+    pub fn set_preferred_phase(&mut self, var: Var, phase: Option<bool>) {
+        self.var_manager.set_preferred_phase(var, phase);
+    }
+
+    /// Drat clauses
+    pub fn drat_clauses(self) -> Option<Vec<DratClause>> {
+        self.drat_clauses.drat_clauses()
+    }
 
-        // Clause is unit under assignment
-        self.watches[p.index()].push(ci);
-        let enqueue_lit = clause.lits[0];
-        self.enqueue(enqueue_lit, Some(ci))
+    /// Flush the proof output, returning the first error met while writing
+    /// the proof. Proof steps after a write error are dropped.
+    pub fn flush_proof(&mut self) -> std::io::Result<()> {
+        self.drat_clauses.flush()
     }
 
     // Only called at top level with empty prop queue
     // Only called on learnt clause
-    fn clause_simplify(&mut self, ci: ClauseIndex) -> bool {
+    fn clause_simplify(&mut self, cr: ClauseRef) -> bool {
+        let size = self.clause_db.size(cr);
         let mut j = 0;
-        let cl = self.clause_db.get_clause_ref(ci);
-        let mut lits = cl.lits.clone();
-        for i in 0..lits.len() {
-            if self.var_manager.value_lit(lits[i]) == LBool::True {
+        for i in 0..size {
+            let lit = self.clause_db.lit(cr, i);
+            if self.var_manager.value_lit(lit) == LBool::True {
                 return true;
-            } else if self.var_manager.value_lit(lits[i]) == LBool::Undef {
-                lits[j] = lits[i];
+            } else if self.var_manager.value_lit(lit) == LBool::Undef {
                 j += 1;
             }
         }
-        while lits.len() != j {
-            lits.pop();
+        if j < size {
+            // The watched literals are unassigned and stay in front
+            let (lit_0, lit_1) = (self.clause_db.lit(cr, 0), self.clause_db.lit(cr, 1));
+            self.watches.detach(cr, lit_0, lit_1, size == 2);
+            let old_lits: Vec<Lit> = self.clause_db.lits(cr).collect();
+            let mut j = 0;
+            for i in 0..size {
+                let lit = self.clause_db.lit(cr, i);
+                if self.var_manager.value_lit(lit) == LBool::Undef {
+                    self.clause_db.set_lit(cr, j, lit);
+                    j += 1;
+                }
+            }
+            self.clause_db.shrink(cr, j);
+            self.watches.attach(cr, lit_0, lit_1, j == 2);
+
+            let new_lits: Vec<Lit> = self.clause_db.lits(cr).collect();
+            let old_id = self.clause_db.id(cr);
+            let mut hints = self.unit_hints(&old_lits);
+            hints.push(old_id);
+            let id = self.clause_db.new_id();
+            self.clause_db.set_id(cr, id);
+            self.drat_clauses.add(id, &new_lits, &hints);
+            self.drat_clauses.delete(old_id, &old_lits);
         }
-        self.clause_db.get_clause_mut_ref(ci).lits = lits;
         false
     }
 
-    fn clause_calc_reason(&mut self, ci: ClauseIndex, p: Option<Lit>) -> Vec<Lit> {
-        // Inv: p == None or p == cl.Lits[0]
-        let cl = self.clause_db.get_clause_ref(ci);
-        debug_assert!(p == None || p == Some(cl.lits[0]));
+    /// Ids of the unit clauses of the literals false at level 0,
+    /// empty if the proof does not need them.
+    fn unit_hints(&mut self, lits: &[Lit]) -> Vec<u64> {
+        if !self.drat_clauses.needs_hints() {
+            return vec![];
+        }
+        let mut hints = vec![];
+        for &lit in lits {
+            if self.var_manager.value_lit(lit) == LBool::False
+                && self.var_manager.get_level(lit.var()) == 0
+            {
+                hints.push(self.unit_id(lit.var()));
+            }
+        }
+        hints
+    }
+
+    /// Id of the unit clause of a variable assigned at level 0. Units implied
+    /// by a reason are added to the proof the first time they are needed,
+    /// so that no clause is derived while original clauses are being added.
+    fn unit_id(&mut self, v: Var) -> u64 {
+        let mut stack = vec![v];
+        while let Some(&v) = stack.last() {
+            if self.unit_ids[v.index()] != 0 {
+                stack.pop();
+                continue;
+            }
+            let cr = self.var_manager.get_reason(v).unwrap();
+            let missing: Vec<Var> = self
+                .clause_db
+                .lits(cr)
+                .map(|lit| lit.var())
+                .filter(|&u| u != v && self.unit_ids[u.index()] == 0)
+                .collect();
+            if missing.is_empty() {
+                let p = Lit::new(v, self.var_manager.value(v) == LBool::False);
+                self.derive_unit_clause(p, cr);
+                stack.pop();
+            } else {
+                stack.extend(missing);
+            }
+        }
+        self.unit_ids[v.index()]
+    }
+
+    /// Add the empty clause to the proof, from a clause falsified at level 0.
+    fn derive_empty_clause(&mut self, lits: &[Lit], id: u64) {
+        let mut hints = self.unit_hints(lits);
+        hints.push(id);
+        let empty_id = self.clause_db.new_id();
+        self.drat_clauses.add(empty_id, &[], &hints);
+    }
+
+    /// Add the unit clause of `p` to the proof, implied at level 0 by the clause `cr`.
+    fn derive_unit_clause(&mut self, p: Lit, cr: ClauseRef) {
+        let lits: Vec<Lit> = self.clause_db.lits(cr).filter(|&lit| lit != p).collect();
+        let mut hints = self.unit_hints(&lits);
+        hints.push(self.clause_db.id(cr));
+        let id = self.clause_db.new_id();
+        self.drat_clauses.add(id, &[p], &hints);
+        self.unit_ids[p.var().index()] = id;
+    }
+
+    fn clause_calc_reason(&mut self, cr: ClauseRef, p: Option<Lit>) -> Vec<Lit> {
+        // Inv: p == None or p == cl.Lits[0], binary clauses may have p at cl.Lits[1]
         let mut reason = vec![];
-        for i in (if p == None { 0 } else { 1 })..cl.lits.len() {
+        for lit in self.clause_db.lits(cr).filter(|&lit| Some(lit) != p) {
             // Inv: self.value_lit(lits[i]) == FALSE
-            debug_assert!(self.var_manager.value_lit(cl.lits[i]) == LBool::False);
-            reason.push(!cl.lits[i]);
+            debug_assert!(self.var_manager.value_lit(lit) == LBool::False);
+            reason.push(!lit);
         }
-        self.clause_db.found_clause_as_reason(ci);
+        self.clause_db.found_clause_as_reason(cr);
         reason
     }
 
-    fn clause_new(&mut self, mut ps: Vec<Lit>, learnt: bool) -> (bool, Option<ClauseIndex>) {
+    /// Add a clause with the given id to the solver, an original one if `lbd`
+    /// is `None`, otherwise a learnt one with the given glue.
+    fn clause_new(
+        &mut self,
+        mut ps: Vec<Lit>,
+        lbd: Option<u32>,
+        id: u64,
+    ) -> (bool, Option<ClauseRef>) {
+        let learnt = lbd.is_some();
         if !learnt {
             // If any lit in ps is true, return true
             for &l in ps.iter() {
@@ -182,18 +410,33 @@ impl Solver {
                 }
             }
 
-            // Remove all false lits from ps
-            ps = ps
+            if ps
                 .iter()
-                .copied()
-                .filter(|&l| self.var_manager.value_lit(l) == LBool::Undef)
-                .collect();
+                .all(|&l| self.var_manager.value_lit(l) == LBool::False)
+            {
+                self.falsified = Some((ps, id));
+                return (false, None);
+            } else if self.drat_clauses.needs_hints() {
+                // Keep the false lits, after the others, for the clause to be
+                // the one of the formula in lrat proofs
+                let var_manager = &self.var_manager;
+                ps.sort_by_key(|&l| var_manager.value_lit(l) == LBool::False);
+            } else {
+                // Remove all false lits from ps
+                ps = ps
+                    .iter()
+                    .copied()
+                    .filter(|&l| self.var_manager.value_lit(l) == LBool::Undef)
+                    .collect();
+            }
         }
 
         if ps.is_empty() {
             (false, None)
         } else if ps.len() == 1 {
-            (self.enqueue(ps[0], None), None)
+            let r = self.enqueue(ps[0], None);
+            self.unit_ids[ps[0].var().index()] = id;
+            (r, None)
         } else {
             if learnt {
                 // Index of the lit with highest decision level
@@ -210,60 +453,114 @@ impl Solver {
                 ps.swap(1, max_i);
             }
 
-            let ci = if !learnt {
-                let ps_0 = ps[0];
-                let ps_1 = ps[1];
-                let ci = self.clause_db.add_original(Clause { lits: ps });
-                self.watches[(!ps_0).index()].push(ci);
-                self.watches[(!ps_1).index()].push(ci);
-                ci
-            } else {
+            let ci = if let Some(lbd) = lbd {
                 self.var_manager.after_learnt_clause(&ps);
-                let ps_0 = ps[0];
-                let ps_1 = ps[1];
-                let ci = self.clause_db.add_learnt(Clause { lits: ps });
-                self.watches[(!ps_0).index()].push(ci);
-                self.watches[(!ps_1).index()].push(ci);
-                ci
+                self.clause_db.add_learnt(&ps, lbd, id)
+            } else {
+                self.clause_db.add_original(&ps, id)
             };
+            self.watches.attach(ci, ps[0], ps[1], ps.len() == 2);
+            if !learnt && self.var_manager.value_lit(ps[1]) == LBool::False {
+                self.enqueue(ps[0], Some(ci));
+            }
 
             (true, Some(ci))
         }
     }
 
     /// Propagate unit clauses in prop_q and return when a confliting clause is found
-    fn propagate(&mut self) -> Option<ClauseIndex> {
-        while !self.prop_q.is_empty() {
-            let p = self.prop_q.pop_back().unwrap();
-            let tmp = self.watches[p.index()].clone();
-            self.watches[p.index()].clear();
-
-            for i in 0..tmp.len() {
-                if !self.clause_propagate(tmp[i], p) {
-                    // Contraint is conflicting
-                    for &c_i in tmp.iter().skip(i + 1) {
-                        self.watches[p.index()].push(c_i);
+    fn propagate(&mut self) -> Option<ClauseRef> {
+        while let Some(p) = self.prop_q.pop_back() {
+            self.stats.propagations += 1;
+            let false_lit = !p;
+
+            // Binary clauses imply the other literal directly
+            for i in 0..self.watches.binary(p).len() {
+                let w = self.watches.binary(p)[i];
+                match self.var_manager.value_lit(w.blocker) {
+                    LBool::True => {}
+                    LBool::False => {
+                        self.prop_q.clear();
+                        return Some(w.cref);
+                    }
+                    LBool::Undef => {
+                        self.enqueue(w.blocker, Some(w.cref));
                     }
-                    self.prop_q.clear();
-                    return Some(tmp[i]);
                 }
             }
 
-            // TODO: There is some bug in below code or this should replace lines
-            // from let tmp = ...
-            // till end of for loop
-            // while !self.watches[p.index()].is_empty() {
-            //     let cl = self.watches[p.index()].pop().unwrap();
-            //     if !self.clause_propagate(cl, p) {
-            //         self.prop_q.clear();
-            //         return Some(cl);
-            //     }
-            // }
+            let mut ws = self.watches.take_long(p);
+            let mut confl = None;
+            let mut i = 0;
+            let mut j = 0;
+            'watches: while i < ws.len() {
+                let w = ws[i];
+                i += 1;
+                // Clause is satisfied, no need to look at it
+                if self.var_manager.value_lit(w.blocker) == LBool::True {
+                    ws[j] = w;
+                    j += 1;
+                    continue;
+                }
+
+                // Deleted clauses are detached lazily
+                let cr = w.cref;
+                if self.clause_db.is_deleted(cr) {
+                    continue;
+                }
+
+                // Make sure false lit at cl.lits[1]
+                if self.clause_db.lit(cr, 0) == false_lit {
+                    self.clause_db.swap_lits(cr, 0, 1);
+                }
+
+                // If 0th watch is true, clause is already satisfied
+                let first = self.clause_db.lit(cr, 0);
+                let new_watcher = Watcher {
+                    cref: cr,
+                    blocker: first,
+                };
+                if first != w.blocker && self.var_manager.value_lit(first) == LBool::True {
+                    ws[j] = new_watcher;
+                    j += 1;
+                    continue;
+                }
+
+                // Look for a new literal to watch
+                for k in 2..self.clause_db.size(cr) {
+                    let lit = self.clause_db.lit(cr, k);
+                    if self.var_manager.value_lit(lit) != LBool::False {
+                        self.clause_db.set_lit(cr, 1, lit);
+                        self.clause_db.set_lit(cr, k, false_lit);
+                        self.watches.push_long(!lit, new_watcher);
+                        continue 'watches;
+                    }
+                }
+
+                // Clause is unit or conflicting under assignment
+                ws[j] = new_watcher;
+                j += 1;
+                if !self.enqueue(first, Some(cr)) {
+                    confl = Some(cr);
+                    while i < ws.len() {
+                        ws[j] = ws[i];
+                        j += 1;
+                        i += 1;
+                    }
+                }
+            }
+            ws.truncate(j);
+            self.watches.put_long(p, ws);
+
+            if confl.is_some() {
+                self.prop_q.clear();
+                return confl;
+            }
         }
         None
     }
 
-    fn enqueue(&mut self, p: Lit, from: Option<ClauseIndex>) -> bool {
+    fn enqueue(&mut self, p: Lit, from: Option<ClauseRef>) -> bool {
         if self.var_manager.value_lit(p) != LBool::Undef {
             !(self.var_manager.value_lit(p) == LBool::False)
         } else {
@@ -275,36 +572,40 @@ impl Solver {
         }
     }
 
-    fn analyze(&mut self, cf: ClauseIndex) -> (Vec<Lit>, i32) {
+    fn analyze(&mut self, cf: ClauseRef) -> (Vec<Lit>, i32, u32) {
         let mut participating_variables: Vec<Var> = vec![];
         let mut reason_variables: HashSet<Var> = HashSet::new();
 
         let mut confl = Some(cf);
-        let mut seen = vec![false; self.n_vars()];
         let mut counter = 0;
         let mut p = None;
 
         let mut out_learnt = vec![UNDEF_LIT]; // Change to asserting literal, later
-        let mut out_btlevel = 0;
+
+        // Antecedents for lrat proofs
+        let lrat = self.drat_clauses.needs_hints();
+        let mut chain = vec![];
+        let mut units = vec![];
         loop {
-            debug_assert!(confl != None, "Conflit cannot be null");
+            debug_assert!(confl.is_some(), "Conflit cannot be null");
             // Inv: confl != NULL
+            if lrat {
+                chain.push(self.clause_db.id(confl.unwrap()));
+            }
             let p_reason = self.clause_calc_reason(confl.unwrap(), p);
+            self.refresh_lbd(confl.unwrap(), p);
 
             // Trace reason for p
             for q in p_reason {
-                if !seen[q.var().index()] {
+                if !self.seen[q.var().index()] {
                     participating_variables.push(q.var());
-                    seen[q.var().index()] = true;
+                    self.seen[q.var().index()] = true;
                     if self.var_manager.get_level(q.var()) == self.decision_level() {
                         counter += 1;
                     } else if self.var_manager.get_level(q.var()) > 0 {
                         out_learnt.push(!q);
-                        out_btlevel = if out_btlevel > self.var_manager.get_level(q.var()) {
-                            out_btlevel
-                        } else {
-                            self.var_manager.get_level(q.var())
-                        };
+                    } else if lrat {
+                        units.push(self.unit_id(q.var()));
                     }
                 }
             }
@@ -315,7 +616,7 @@ impl Solver {
                 let v = p.unwrap().var();
                 confl = self.var_manager.get_reason(v);
                 self.var_manager.reset(v);
-                if seen[v.index()] {
+                if self.seen[v.index()] {
                     break;
                 }
             }
@@ -326,13 +627,62 @@ impl Solver {
             }
         }
         out_learnt[0] = !(p.unwrap());
-        if !seen[out_learnt[0].var().index()] {
+        if !self.seen[out_learnt[0].var().index()] {
             participating_variables.push(out_learnt[0].var());
         }
+
+        let mut to_clear = vec![];
+        let learnt_len = out_learnt.len();
+        let unminimized = if lrat { out_learnt.clone() } else { vec![] };
+        match self.learnt_minimization {
+            LearntMinimization::None => {}
+            LearntMinimization::Local => {
+                let mut j = 1;
+                for i in 1..out_learnt.len() {
+                    if !self.is_locally_redundant(out_learnt[i]) {
+                        out_learnt[j] = out_learnt[i];
+                        j += 1;
+                    }
+                }
+                out_learnt.truncate(j);
+            }
+            LearntMinimization::Recursive => {
+                let abstract_levels = out_learnt[1..]
+                    .iter()
+                    .fold(0, |acc, lit| acc | self.abstract_level(lit.var()));
+                let mut j = 1;
+                for i in 1..out_learnt.len() {
+                    let lit = out_learnt[i];
+                    if self.var_manager.get_reason(lit.var()).is_none()
+                        || !self.lit_redundant(lit, abstract_levels, &mut to_clear)
+                    {
+                        out_learnt[j] = lit;
+                        j += 1;
+                    }
+                }
+                out_learnt.truncate(j);
+            }
+        }
+        if lrat {
+            self.learnt_hints(&unminimized, &out_learnt, units, chain);
+        }
+        self.stats.learnt_literals += out_learnt.len() as u64;
+        self.stats.minimized_literals += (learnt_len - out_learnt.len()) as u64;
+        let out_btlevel = out_learnt[1..]
+            .iter()
+            .map(|lit| self.var_manager.get_level(lit.var()))
+            .max()
+            .unwrap_or(0);
+
+        for v in participating_variables.iter() {
+            self.seen[v.index()] = false;
+        }
+        for lit in to_clear {
+            self.seen[lit.var().index()] = false;
+        }
         for lit in out_learnt.iter() {
             if let Some(ci) = self.var_manager.get_reason(lit.var()) {
-                let clause = self.clause_db.get_clause_ref(ci);
-                for lit in clause.lits.iter() {
+                for lit in self.clause_db.lits(ci) {
                     reason_variables.insert(lit.var());
                 }
             }
@@ -342,14 +692,129 @@ impl Solver {
         }
         self.var_manager
             .after_conflict_analysis(participating_variables, reason_variables);
-        (out_learnt, out_btlevel)
+        // Asserting literal is the only one at the current decision level
+        let lbd = self
+            .var_manager
+            .compute_lbd(out_learnt[1..].iter().copied())
+            + 1;
+        (out_learnt, out_btlevel, lbd)
     }
 
-    fn record(&mut self, clause: Vec<Lit>) {
+    /// Set the antecedents of the learnt clause for lrat proofs, given the
+    /// level 0 units and the clauses resolved during conflict analysis.
+    /// Literals removed by minimization are implied by the reasons visited
+    /// walking the trail backwards. Must be called before `seen` is cleared.
+    fn learnt_hints(
+        &mut self,
+        unminimized: &[Lit],
+        learnt: &[Lit],
+        mut units: Vec<u64>,
+        chain: Vec<u64>,
+    ) {
+        let in_clause: HashSet<Var> = learnt.iter().map(|lit| lit.var()).collect();
+        let mut needed: HashSet<Var> = unminimized
+            .iter()
+            .map(|lit| lit.var())
+            .filter(|v| !in_clause.contains(v))
+            .collect();
+        let mut level_0: HashSet<Var> = HashSet::new();
+        let mut pending = needed.len();
+        let mut min_chain = vec![];
+        let mut i = self.trail.trail_len();
+        while pending > 0 {
+            i -= 1;
+            let v = self.trail.lit(i).var();
+            if !needed.contains(&v) {
+                continue;
+            }
+            pending -= 1;
+            let cr = self.var_manager.get_reason(v).unwrap();
+            min_chain.push(self.clause_db.id(cr));
+            let reason: Vec<Lit> = self.clause_db.lits(cr).collect();
+            for q in reason {
+                let u = q.var();
+                if u == v || in_clause.contains(&u) {
+                    continue;
+                }
+                if self.var_manager.get_level(u) == 0 {
+                    if !self.seen[u.index()] && level_0.insert(u) {
+                        units.push(self.unit_id(u));
+                    }
+                } else if needed.insert(u) {
+                    pending += 1;
+                }
+            }
+        }
+
+        self.hints = units;
+        self.hints.extend(min_chain.iter().rev());
+        self.hints.extend(chain.iter().rev());
+    }
+
+    fn abstract_level(&self, v: Var) -> u32 {
+        1 << (self.var_manager.get_level(v) & 31)
+    }
+
+    /// A literal of the learnt clause is redundant if all other literals
+    /// of its reason are in the learnt clause or at level 0.
+    fn is_locally_redundant(&self, p: Lit) -> bool {
+        match self.var_manager.get_reason(p.var()) {
+            None => false,
+            Some(ci) => self.clause_db.lits(ci).all(|q| {
+                q.var() == p.var()
+                    || self.seen[q.var().index()]
+                    || self.var_manager.get_level(q.var()) == 0
+            }),
+        }
+    }
+
+    /// Check if `p` can be removed from the learnt clause, i.e. it is implied
+    /// by the other literals of the clause through a chain of reasons.
+    /// `abstract_levels` is used to abort early if the chain reaches a
+    /// decision level not present in the clause.
+    fn lit_redundant(&mut self, p: Lit, abstract_levels: u32, to_clear: &mut Vec<Lit>) -> bool {
+        let mut stack = vec![p];
+        let top = to_clear.len();
+        while let Some(q) = stack.pop() {
+            let ci = self.var_manager.get_reason(q.var()).unwrap();
+            for i in 0..self.clause_db.size(ci) {
+                let l = self.clause_db.lit(ci, i);
+                let v = l.var();
+                if v == q.var() || self.seen[v.index()] || self.var_manager.get_level(v) == 0 {
+                    continue;
+                }
+                if self.var_manager.get_reason(v).is_some()
+                    && (self.abstract_level(v) & abstract_levels) != 0
+                {
+                    self.seen[v.index()] = true;
+                    stack.push(l);
+                    to_clear.push(l);
+                } else {
+                    for lit in to_clear.drain(top..) {
+                        self.seen[lit.var().index()] = false;
+                    }
+                    return false;
+                }
+            }
+        }
+        true
+    }
+
+    /// Recompute the LBD of a learnt clause taking part in conflict analysis.
+    /// All its literals are assigned except `p`, which was at the conflict level.
+    fn refresh_lbd(&mut self, ci: ClauseRef, p: Option<Lit>) {
+        if self.clause_db.lbd(ci).is_some() {
+            let lbd = self.var_manager.compute_lbd(self.clause_db.lits(ci)) + p.is_some() as u32;
+            self.clause_db.update_lbd(ci, lbd);
+        }
+    }
+
+    fn record(&mut self, clause: Vec<Lit>, lbd: u32) {
         // Added here because clause_new doesn't add unit clauses to clause_db
-        self.drat_clauses.capture(&clause, false);
+        let id = self.clause_db.new_id();
+        self.drat_clauses.add(id, &clause, &self.hints);
         let asserting_lit = clause[0];
-        let (_, c) = self.clause_new(clause, true);
+        let (_, c) = self.clause_new(clause, Some(lbd), id);
         self.enqueue(asserting_lit, c);
     }
 
@@ -368,59 +833,98 @@ impl Solver {
     }
 
     fn cancel_until(&mut self, level: i32) {
+        if self.trail.decision_level() > level {
+            self.assump_head = 0;
+        }
         while self.trail.decision_level() > level {
             self.cancel();
         }
     }
 
-    fn search(&mut self, nof_conflicts: u32, nof_learnts: u32) -> (LBool, Vec<bool>) {
-        let mut conflit_count = 0;
-
+    fn search(&mut self) -> (LBool, Vec<bool>) {
         loop {
             let confl = self.propagate();
             match confl {
                 // Conflit
                 Some(c) => {
-                    conflit_count += 1;
-                    if self.decision_level() == self.root_level {
+                    self.stats.conflicts += 1;
+                    if self.decision_level() == 0 {
+                        let lits: Vec<Lit> = self.clause_db.lits(c).collect();
+                        self.derive_empty_clause(&lits, self.clause_db.id(c));
+                        self.undef_state = true;
                         return (LBool::False, vec![]);
                     }
-                    let (learnt_clause, backtrack_level) = self.analyze(c);
-                    self.cancel_until(if backtrack_level > self.root_level {
-                        backtrack_level
-                    } else {
-                        self.root_level
-                    });
-                    self.record(learnt_clause);
+                    let trail_len = self.n_assigns();
+                    let (learnt_clause, backtrack_level, lbd) = self.analyze(c);
+                    self.restarter.on_conflict(lbd, trail_len);
+                    self.cancel_until(backtrack_level);
+                    self.record(learnt_clause, lbd);
                     self.var_manager.after_record_learnt_clause();
                     self.clause_db.after_record_learnt_clause();
+
+                    self.learntsize_adjust_cnt -= 1;
+                    if self.learntsize_adjust_cnt == 0 {
+                        self.learntsize_adjust_confl *= 1.5;
+                        self.learntsize_adjust_cnt = self.learntsize_adjust_confl as u32;
+                        self.max_learnts *= 1.1;
+                    }
                 }
                 // No Conflict
                 None => {
                     if self.decision_level() == 0 {
                         self.simplify_db();
+                        if self.stats.conflicts >= self.next_inprocess && !self.inprocess() {
+                            return (LBool::False, vec![]);
+                        }
                     }
 
-                    if self.clause_db.learnts_len() as i32 - self.n_assigns() as i32
-                        >= nof_learnts as i32
+                    if self.clause_db.reducible_learnts_len() as f64 - self.n_assigns() as f64
+                        >= self.max_learnts
                     {
                         self.reduce_db();
                     }
 
-                    if self.n_assigns() == self.n_vars() {
-                        // Model found
-                        let model = self.var_manager.model();
-                        self.cancel_until(self.root_level);
-                        return (LBool::True, model);
-                    } else if conflit_count >= nof_conflicts {
-                        // Force a restart
-                        self.cancel_until(self.root_level);
-
+                    if self.restarter.should_restart() || !self.within_budget() {
+                        // Force a restart, or stop at the resource limits
+                        self.cancel_until(0);
                         return (LBool::Undef, vec![]);
-                    } else {
-                        // New variable decision
-                        let p = Lit::new(self.var_manager.select_var(), false);
+                    }
+
+                    // Assumptions are decided before any other variable.
+                    // Assumptions that are already true do not open a decision level.
+                    let mut next = None;
+                    while self.assump_head < self.assumptions.len() {
+                        let p = self.assumptions[self.assump_head];
+                        match self.var_manager.value_lit(p) {
+                            LBool::True => self.assump_head += 1,
+                            LBool::False => {
+                                self.analyze_final(&[p]);
+                                self.conflict.push(p);
+                                self.cancel_until(0);
+                                return (LBool::False, vec![]);
+                            }
+                            LBool::Undef => {
+                                next = Some(p);
+                                break;
+                            }
+                        }
+                    }
+
+                    if next.is_none() {
+                        next = self.var_manager.select_lit();
+                        if next.is_some() {
+                            // New variable decision
+                            self.stats.decisions += 1;
+                        }
+                    }
+
+                    if let Some(p) = next {
                         self.assume(p);
+                    } else {
+                        // Model found, all decision variables are assigned
+                        let model = self.var_manager.model();
+                        self.cancel_until(0);
+                        return (LBool::True, model);
                     }
                 }
             }
@@ -430,6 +934,8 @@ impl Solver {
     fn reduce_db(&mut self) {
         self.clause_db
             .reduce_db(&self.var_manager, &mut self.watches, &mut self.drat_clauses);
+        self.clause_db
+            .garbage_collect(&mut self.watches, &mut self.var_manager);
     }
 
     fn simplify_db(&mut self) -> bool {
@@ -437,63 +943,691 @@ impl Solver {
             return false;
         }
 
-        let cls = self.clause_db.learnt_indices();
-        for i in cls {
-            if self.clause_simplify(ClauseIndex::Lrnt(i)) {
+        // Satisfied clauses are removed, including the reasons of level 0 units
+        if self.drat_clauses.needs_hints() {
+            for i in 0..self.trail.trail_len() {
+                self.unit_id(self.trail.lit(i).var());
+            }
+        }
+
+        for cr in self.clause_db.learnt_refs() {
+            if self.clause_simplify(cr) {
                 self.clause_db
-                    .remove_learnt(i, &mut self.watches, &mut self.drat_clauses);
+                    .remove(cr, &mut self.watches, &mut self.drat_clauses);
+            }
+        }
+        self.clause_db.purge_learnts();
+        self.clause_db
+            .garbage_collect(&mut self.watches, &mut self.var_manager);
+        true
+    }
+
+    /// Simplify the clauses at level 0 during search.
+    /// Returns false if the formula is found unsatisfiable.
+    fn inprocess(&mut self) -> bool {
+        self.next_inprocess = self.stats.conflicts + self.inprocess.interval;
+        if self.inprocess.probing && !self.probe() {
+            return false;
+        }
+        if self.inprocess.substitution && !self.substitute() {
+            return false;
+        }
+        !self.inprocess.vivification || self.vivify()
+    }
+
+    /// Probe the literals which imply others by binary clauses, until the
+    /// propagation budget is spent. For the roots of the binary implication
+    /// graph only one polarity of the variable is probed, otherwise both are
+    /// and the literals they both imply are necessary assignments.
+    /// Returns false if the formula is found unsatisfiable.
+    fn probe(&mut self) -> bool {
+        let budget = self.stats.propagations + self.inprocess.probing_effort;
+        let n_vars = self.n_vars();
+        for _ in 0..n_vars {
+            if self.stats.propagations > budget {
+                break;
+            }
+            let v = Var::new(self.probe_head);
+            self.probe_head = (self.probe_head + 1) % n_vars;
+            if self.var_manager.value(v) != LBool::Undef || !self.var_manager.is_decision(v) {
+                continue;
+            }
+            let probes: Vec<Lit> = [v.pos_lit(), v.neg_lit()]
+                .iter()
+                .copied()
+                .filter(|&lit| !self.watches.binary(lit).is_empty())
+                .collect();
+            if probes.is_empty() {
+                continue;
+            }
+
+            // Literals implied by each probe, then units implied by both
+            let implied = match self.probe_lit(probes[0]) {
+                Some(implied) => implied,
+                None if self.undef_state => return false,
+                None => continue,
+            };
+            if probes.len() == 1 {
+                continue;
+            }
+            let implied: HashSet<usize> = implied.iter().map(|lit| lit.index()).collect();
+            let necessary: Vec<Lit> = match self.probe_lit(probes[1]) {
+                Some(lits) => lits
+                    .into_iter()
+                    .filter(|lit| implied.contains(&lit.index()))
+                    .collect(),
+                None if self.undef_state => return false,
+                None => continue,
+            };
+            for lit in necessary {
+                let binaries = [
+                    (self.clause_db.new_id(), vec![v.neg_lit(), lit]),
+                    (self.clause_db.new_id(), vec![v.pos_lit(), lit]),
+                ];
+                for (id, lits) in binaries.iter() {
+                    self.drat_clauses.add(*id, lits, &[]);
+                }
+                let id = self.clause_db.new_id();
+                self.drat_clauses.add(id, &[lit], &[]);
+                for (id, lits) in binaries.iter() {
+                    self.drat_clauses.delete(*id, lits);
+                }
+                self.clause_new(vec![lit], Some(1), id);
+                self.stats.necessary_assignments += 1;
+            }
+            if !self.propagate_units() {
+                return false;
             }
         }
         true
     }
 
+    /// Propagate the literal at level 1 and return the literals it implies.
+    /// If the literal fails, the unit learnt from the conflict is propagated
+    /// at level 0 and `None` is returned, with `undef_state` set if the
+    /// formula is found unsatisfiable.
+    fn probe_lit(&mut self, p: Lit) -> Option<Vec<Lit>> {
+        self.trail.new_dl();
+        self.enqueue(p, None);
+        if let Some(confl) = self.propagate() {
+            let (learnt_clause, _, lbd) = self.analyze(confl);
+            self.cancel_until(0);
+            self.record(learnt_clause, lbd);
+            self.stats.failed_literals += 1;
+            self.propagate_units();
+            return None;
+        }
+
+        let start = self.trail.level_start(1);
+        let implied: Vec<Lit> = (start + 1..self.trail.trail_len())
+            .map(|i| self.trail.lit(i))
+            .collect();
+        let resolvents = if self.inprocess.hyper_binary_resolution {
+            self.hyper_binary_resolvents()
+        } else {
+            vec![]
+        };
+        self.cancel_until(0);
+
+        // Removable, as they are implied by the reasons and the binary clauses
+        let lbd = self.clause_db.reducible_lbd();
+        for lits in resolvents {
+            let id = self.clause_db.new_id();
+            self.drat_clauses.add(id, &lits, &[]);
+            let cr = self.clause_db.add_learnt(&lits, lbd, id);
+            self.watches.attach(cr, lits[0], lits[1], true);
+            self.stats.hyper_binary_resolvents += 1;
+        }
+        Some(implied)
+    }
+
+    /// Binary clauses `[!dom, lit]` for the literals implied at level 1 by
+    /// long clauses, where `dom` is the closest dominator of the literals
+    /// falsifying the reason in the binary implication tree of the probe.
+    /// Implications already given by a binary clause are left out.
+    fn hyper_binary_resolvents(&self) -> Vec<[Lit; 2]> {
+        let start = self.trail.level_start(1);
+        let root = self.trail.lit(start);
+        // Parent and depth of the literals of level 1, by variable
+        let mut tree = HashMap::new();
+        tree.insert(root.var().index(), (root, 0));
+        let dominator = |tree: &HashMap<usize, (Lit, usize)>, mut a: Lit, mut b: Lit| {
+            while a != b {
+                let (parent_a, depth_a) = tree[&a.var().index()];
+                let (parent_b, depth_b) = tree[&b.var().index()];
+                if depth_a >= depth_b {
+                    a = parent_a;
+                } else {
+                    b = parent_b;
+                }
+            }
+            a
+        };
+
+        let mut resolvents = vec![];
+        for i in start + 1..self.trail.trail_len() {
+            let lit = self.trail.lit(i);
+            let cr = self.var_manager.get_reason(lit.var()).unwrap();
+            let antecedents: Vec<Lit> = self
+                .clause_db
+                .lits(cr)
+                .filter(|&l| l != lit && self.var_manager.get_level(l.var()) > 0)
+                .map(|l| !l)
+                .collect();
+            let dom = match antecedents.split_first() {
+                Some((&first, rest)) => rest.iter().fold(first, |dom, &a| dominator(&tree, dom, a)),
+                None => root,
+            };
+            if antecedents.len() > 1 && !self.watches.binary(dom).iter().any(|w| w.blocker == lit) {
+                resolvents.push([!dom, lit]);
+            }
+            let depth = tree[&dom.var().index()].1 + 1;
+            tree.insert(lit.var().index(), (dom, depth));
+        }
+        resolvents
+    }
+
+    /// Propagate the units at level 0. If a conflict is found, the empty
+    /// clause is derived and false is returned.
+    fn propagate_units(&mut self) -> bool {
+        match self.propagate() {
+            Some(c) => {
+                let lits: Vec<Lit> = self.clause_db.lits(c).collect();
+                self.derive_empty_clause(&lits, self.clause_db.id(c));
+                self.undef_state = true;
+                false
+            }
+            None => true,
+        }
+    }
+
+    /// Strongly connected components of the binary implication graph
+    /// over the unassigned decision variables, with more than one literal.
+    fn equivalent_lits(&self) -> Vec<Vec<Lit>> {
+        let to_lit = |i: usize| Lit::new(Var::new(i / 2), i % 2 == 1);
+        let is_node = |lit: Lit| {
+            self.var_manager.value_lit(lit) == LBool::Undef
+                && self.var_manager.is_decision(lit.var())
+        };
+        let n_lits = 2 * self.n_vars();
+        let mut index = vec![usize::MAX; n_lits];
+        let mut low = vec![0; n_lits];
+        let mut on_stack = vec![false; n_lits];
+        let mut stack = vec![];
+        let mut sccs = vec![];
+        let mut counter = 0;
+
+        // Iterative Tarjan's algorithm, with the next edge of each node on the path
+        for root in 0..n_lits {
+            if index[root] != usize::MAX || !is_node(to_lit(root)) {
+                continue;
+            }
+            let mut path = vec![(root, 0)];
+            index[root] = counter;
+            low[root] = counter;
+            counter += 1;
+            stack.push(root);
+            on_stack[root] = true;
+            while let Some(&mut (v, ref mut next)) = path.last_mut() {
+                let edges = self.watches.binary(to_lit(v));
+                if *next < edges.len() {
+                    let w = edges[*next].blocker;
+                    *next += 1;
+                    if !is_node(w) {
+                        continue;
+                    }
+                    let w = w.index();
+                    if index[w] == usize::MAX {
+                        index[w] = counter;
+                        low[w] = counter;
+                        counter += 1;
+                        stack.push(w);
+                        on_stack[w] = true;
+                        path.push((w, 0));
+                    } else if on_stack[w] {
+                        low[v] = low[v].min(index[w]);
+                    }
+                    continue;
+                }
+                path.pop();
+                if let Some(&(u, _)) = path.last() {
+                    low[u] = low[u].min(low[v]);
+                }
+                if low[v] == index[v] {
+                    let mut scc = vec![];
+                    while let Some(w) = stack.pop() {
+                        on_stack[w] = false;
+                        scc.push(to_lit(w));
+                        if w == v {
+                            break;
+                        }
+                    }
+                    if scc.len() > 1 {
+                        sccs.push(scc);
+                    }
+                }
+            }
+        }
+        sccs
+    }
+
+    /// Replace the literals equivalent to another one in all the clauses.
+    /// The representative of a component is one of its frozen or assumed
+    /// variables if any, otherwise the one of smallest index.
+    /// Returns false if the formula is found unsatisfiable.
+    fn substitute(&mut self) -> bool {
+        let mut fixed = self.frozen.clone();
+        for lit in self.assumptions.iter() {
+            fixed[lit.var().index()] = true;
+        }
+        let mut repr: Vec<Lit> = (0..2 * self.n_vars())
+            .map(|i| Lit::new(Var::new(i / 2), i % 2 == 1))
+            .collect();
+        let mut done = vec![false; self.n_vars()];
+        let mut substituted = vec![];
+        for scc in self.equivalent_lits() {
+            if done[scc[0].var().index()] {
+                continue;
+            }
+            for &lit in scc.iter() {
+                done[lit.var().index()] = true;
+            }
+
+            // A literal equivalent to its negation is false
+            if let Some(&lit) = scc.iter().find(|&&lit| scc.contains(&!lit)) {
+                let id = self.clause_db.new_id();
+                self.drat_clauses.add(id, &[!lit], &[]);
+                self.clause_new(vec![!lit], Some(1), id);
+                return self.propagate_units();
+            }
+            let r = scc
+                .iter()
+                .copied()
+                .find(|lit| fixed[lit.var().index()])
+                .unwrap_or_else(|| *scc.iter().min_by_key(|lit| lit.var().index()).unwrap());
+            for &lit in scc.iter() {
+                if lit != r && !fixed[lit.var().index()] {
+                    repr[lit.index()] = r;
+                    repr[(!lit).index()] = !r;
+                    substituted.push((lit, r));
+                }
+            }
+        }
+        if substituted.is_empty() {
+            return true;
+        }
+
+        // The rewritten clauses are all added to the proof before the old
+        // ones are deleted, for the equivalences to be implied
+        let mut rewritten = vec![];
+        for cr in self
+            .clause_db
+            .original_refs()
+            .into_iter()
+            .chain(self.clause_db.learnt_refs())
+        {
+            if !self.clause_db.lits(cr).any(|l| repr[l.index()] != l) {
+                continue;
+            }
+            let mut lits: Vec<Lit> = self
+                .clause_db
+                .lits(cr)
+                .map(|l| repr[l.index()])
+                .filter(|&l| self.var_manager.value_lit(l) != LBool::False)
+                .collect();
+            lits.sort_by_key(|l| l.index());
+            lits.dedup();
+            let satisfied = lits.windows(2).any(|w| w[0] == !w[1])
+                || lits
+                    .iter()
+                    .any(|&l| self.var_manager.value_lit(l) == LBool::True);
+            let id = self.clause_db.new_id();
+            if !satisfied {
+                self.drat_clauses.add(id, &lits, &[]);
+            }
+            rewritten.push((cr, lits, satisfied, id));
+        }
+        for &(cr, ..) in rewritten.iter() {
+            self.clause_db
+                .remove(cr, &mut self.watches, &mut self.drat_clauses);
+        }
+        for (cr, lits, satisfied, id) in rewritten {
+            if satisfied {
+                continue;
+            }
+            match self.clause_db.lbd(cr) {
+                Some(lbd) if lits.len() > 1 => {
+                    let ci = self.clause_db.add_learnt(&lits, lbd, id);
+                    self.watches.attach(ci, lits[0], lits[1], lits.len() == 2);
+                }
+                _ => {
+                    let (r, _) = self.clause_new(lits, None, id);
+                    if !r {
+                        self.undef_state = true;
+                        return false;
+                    }
+                }
+            }
+        }
+
+        // The model extension gives each variable the value of its representative
+        for (lit, r) in substituted {
+            self.reconstruction.push(lit, vec![lit, !r]);
+            self.reconstruction.push(!lit, vec![!lit, r]);
+            self.var_manager.set_decision(lit.var(), false);
+            self.stats.substituted_vars += 1;
+        }
+        self.clause_db.purge_learnts();
+        self.clause_db
+            .garbage_collect(&mut self.watches, &mut self.var_manager);
+        self.propagate_units()
+    }
+
+    /// Vivify the clauses not vivified yet, the learnts by increasing LBD
+    /// then the original ones, until the propagation budget is spent.
+    /// Returns false if the formula is found unsatisfiable.
+    fn vivify(&mut self) -> bool {
+        let budget = self.stats.propagations + self.inprocess.vivification_effort;
+        let mut learnts = self.clause_db.learnt_refs();
+        learnts.sort_by_key(|&cr| self.clause_db.lbd(cr));
+        let clauses: Vec<ClauseRef> = learnts
+            .into_iter()
+            .chain(self.clause_db.original_refs())
+            .filter(|&cr| !self.clause_db.is_vivified(cr))
+            .collect();
+        for cr in clauses {
+            if self.stats.propagations > budget {
+                break;
+            }
+            if !self.vivify_clause(cr) {
+                return false;
+            }
+        }
+        self.clause_db.purge_learnts();
+        self.clause_db
+            .garbage_collect(&mut self.watches, &mut self.var_manager);
+        true
+    }
+
+    /// Propagate the negations of the literals of the clause one at a time,
+    /// without the clause, and shorten it to the literals assigned before
+    /// a conflict or before one of its literals is implied true. Literals
+    /// implied false are left out. Returns false if the formula is found
+    /// unsatisfiable.
+    fn vivify_clause(&mut self, cr: ClauseRef) -> bool {
+        self.clause_db.set_vivified(cr);
+        let lits: Vec<Lit> = self.clause_db.lits(cr).collect();
+        if lits
+            .iter()
+            .any(|&l| self.var_manager.value_lit(l) == LBool::True)
+        {
+            return true;
+        }
+        self.watches.detach(cr, lits[0], lits[1], lits.len() == 2);
+        let mut kept = vec![];
+        for &lit in lits.iter() {
+            match self.var_manager.value_lit(lit) {
+                LBool::True => {
+                    kept.push(lit);
+                    break;
+                }
+                LBool::False => {}
+                LBool::Undef => {
+                    kept.push(lit);
+                    self.trail.new_dl();
+                    self.enqueue(!lit, None);
+                    if self.propagate().is_some() {
+                        break;
+                    }
+                }
+            }
+        }
+        self.cancel_until(0);
+        if kept.len() == lits.len() {
+            self.watches.attach(cr, lits[0], lits[1], lits.len() == 2);
+            return true;
+        }
+
+        self.stats.vivified_clauses += 1;
+        self.stats.vivified_literals += (lits.len() - kept.len()) as u64;
+        let id = self.clause_db.new_id();
+        self.drat_clauses.add(id, &kept, &[]);
+        let lbd = self.clause_db.lbd(cr);
+        self.clause_db
+            .remove(cr, &mut self.watches, &mut self.drat_clauses);
+        match lbd {
+            Some(lbd) if kept.len() > 1 => {
+                let lbd = lbd.min(kept.len() as u32);
+                let ci = self.clause_db.add_learnt(&kept, lbd, id);
+                self.watches.attach(ci, kept[0], kept[1], kept.len() == 2);
+                true
+            }
+            _ => {
+                self.clause_new(kept, None, id);
+                self.propagate_units()
+            }
+        }
+    }
+
+    /// Eliminate variables and clauses from the original clauses at level 0.
+    /// Returns false if the formula is found unsatisfiable.
+    fn preprocess(&mut self) -> bool {
+        let options = &self.preprocess;
+        if !(options.elimination || options.blocked_clauses || options.covered_clauses)
+            || !self.preprocess_pending
+        {
+            return true;
+        }
+        self.preprocess_pending = false;
+
+        // Satisfied clauses are removed, false literals are left out
+        let mut refs = vec![];
+        let mut clauses = vec![];
+        for cr in self.clause_db.original_refs() {
+            let lits: Vec<Lit> = self.clause_db.lits(cr).collect();
+            if lits
+                .iter()
+                .any(|&l| self.var_manager.value_lit(l) == LBool::True)
+            {
+                self.clause_db
+                    .remove(cr, &mut self.watches, &mut self.drat_clauses);
+                continue;
+            }
+            refs.push(cr);
+            clauses.push(
+                lits.into_iter()
+                    .filter(|&l| self.var_manager.value_lit(l) == LBool::Undef)
+                    .collect(),
+            );
+        }
+
+        let mut candidates: Vec<bool> = (0..self.n_vars())
+            .map(Var::new)
+            .map(|v| {
+                !self.frozen[v.index()]
+                    && self.var_manager.is_decision(v)
+                    && self.var_manager.value(v) == LBool::Undef
+            })
+            .collect();
+        for lit in self.assumptions.iter() {
+            candidates[lit.var().index()] = false;
+        }
+        let result = elim::eliminate(
+            clauses,
+            &candidates,
+            &self.preprocess,
+            &mut self.drat_clauses,
+            &mut self.reconstruction,
+        );
+        self.stats.eliminated_vars += result.eliminated.len() as u64;
+        self.stats.subsumed_clauses += result.subsumed;
+        self.stats.strengthened_clauses += result.strengthened;
+        self.stats.blocked_clauses += result.blocked;
+        self.stats.covered_clauses += result.covered;
+        if result.unsat {
+            self.undef_state = true;
+            return false;
+        }
+
+        for &v in result.eliminated.iter() {
+            self.var_manager.set_decision(v, false);
+        }
+        for lits in result.added {
+            let id = self.clause_db.new_id();
+            let (r, _) = self.clause_new(lits, None, id);
+            if !r {
+                self.undef_state = true;
+            }
+        }
+        for i in result.removed {
+            self.clause_db
+                .remove(refs[i], &mut self.watches, &mut self.drat_clauses);
+        }
+
+        // Learnt clauses are not implied by the remaining clauses
+        // when they contain eliminated variables
+        for cr in self.clause_db.learnt_refs() {
+            if self
+                .clause_db
+                .lits(cr)
+                .any(|l| !self.var_manager.is_decision(l.var()))
+            {
+                self.clause_db
+                    .remove(cr, &mut self.watches, &mut self.drat_clauses);
+            }
+        }
+        self.clause_db.purge_learnts();
+        self.clause_db
+            .garbage_collect(&mut self.watches, &mut self.var_manager);
+        !self.undef_state
+    }
+
     /// Solve the SAT formula under given assumptions.
+    /// Returns `Solution::Unknown` if a resource limit is reached.
+    /// Panics if the self-check of the model fails.
     pub fn solve(&mut self, assumps: Vec<Lit>) -> Solution {
+        match self.try_solve(assumps) {
+            Ok(solution) => solution,
+            Err(e) => panic!("internal error: {}", e),
+        }
+    }
+
+    /// Solve the SAT formula under given assumptions. In self-check mode,
+    /// returns an error if the model does not satisfy the clauses given to
+    /// `add_clause` or the assumptions.
+    pub fn try_solve(&mut self, assumps: Vec<Lit>) -> Result<Solution, InternalError> {
+        self.conflict.clear();
         let solution = self.solve_(assumps);
-        if let Solution::Unsat = solution {
-            self.drat_clauses.capture(&[], false);
+        if let (Solution::Sat(model), Some(clauses)) = (&solution, &self.checked_clauses) {
+            check_model(clauses, &self.assumptions, model)?;
+        }
+        Ok(solution)
+    }
+
+    /// Returns the assumptions of the last call to `solve` that were found
+    /// responsible for `Solution::Unsat`, i.e. the formula is unsatisfiable
+    /// under this subset of the assumptions.
+    /// It is empty if the formula is unsatisfiable without assumptions.
+    pub fn failed_assumptions(&self) -> &[Lit] {
+        &self.conflict
+    }
+
+    /// Compute the assumptions implying the negation of the given false literals.
+    /// Only called when all decisions on the trail are assumptions,
+    /// so the literals without reason above level 0 are assumptions.
+    fn analyze_final(&mut self, lits: &[Lit]) {
+        self.conflict.clear();
+        if self.decision_level() == 0 {
+            return;
+        }
+
+        for lit in lits {
+            if self.var_manager.get_level(lit.var()) > 0 {
+                self.seen[lit.var().index()] = true;
+            }
+        }
+
+        for i in (self.trail.level_start(1)..self.trail.trail_len()).rev() {
+            let lit = self.trail.lit(i);
+            let v = lit.var();
+            if self.seen[v.index()] {
+                match self.var_manager.get_reason(v) {
+                    None => self.conflict.push(lit),
+                    Some(cr) => {
+                        for q in self.clause_db.lits(cr) {
+                            if self.var_manager.get_level(q.var()) > 0 {
+                                self.seen[q.var().index()] = true;
+                            }
+                        }
+                    }
+                }
+                self.seen[v.index()] = false;
+            }
         }
-        solution
     }
 
     fn solve_(&mut self, assumps: Vec<Lit>) -> Solution {
+        self.restore(&assumps);
         if self.undef_state {
+            if let Some((lits, id)) = self.falsified.take() {
+                self.derive_empty_clause(&lits, id);
+            }
             return Solution::Unsat;
         }
-        let restart_first = 100.0;
-        let restart_inc = 2.0f64;
-        let mut nof_learnts: f64 = (self.n_clauses() as f64) / 3.0;
+        self.max_learnts = (self.n_clauses() as f64) / 3.0;
+        self.learntsize_adjust_confl = 100.0;
+        self.learntsize_adjust_cnt = 100;
+        self.restarter.reset_schedule();
+        self.budget_start = Usage {
+            start: Instant::now(),
+            conflicts: self.stats.conflicts,
+            propagations: self.stats.propagations,
+            flips: 0,
+            checks: 0,
+            timed_out: false,
+        };
         let mut status = LBool::Undef;
 
-        // Push incremental assumptions
-        for assump in assumps {
-            if !self.assume(assump) || self.propagate().is_some() {
-                self.cancel_until(0);
-                return Solution::Unsat;
+        if let Some(c) = self.propagate() {
+            let lits: Vec<Lit> = self.clause_db.lits(c).collect();
+            self.derive_empty_clause(&lits, self.clause_db.id(c));
+            self.undef_state = true;
+            return Solution::Unsat;
+        }
+
+        // Assumptions are decided by search
+        self.assumptions = assumps;
+        self.assump_head = 0;
+
+        if !self.preprocess() {
+            if let Some((lits, id)) = self.falsified.take() {
+                self.derive_empty_clause(&lits, id);
             }
+            return Solution::Unsat;
         }
-        self.root_level = self.decision_level();
 
         let mut model = vec![];
 
         // Solve
-        let mut curr_restarts = 0;
-        while status == LBool::Undef {
-            let rest_base = restart_inc.powi(curr_restarts);
-            let nof_conflicts = rest_base * restart_first;
-            let res = self.search(nof_conflicts as u32, nof_learnts as u32);
+        while status == LBool::Undef && self.within_budget() {
+            let res = self.search();
             status = res.0;
             model = res.1;
-            nof_learnts *= 1.1;
-            curr_restarts += 1;
+            if status == LBool::Undef {
+                self.stats.restarts += 1;
+            }
+            self.restarter.on_restart();
         }
 
         self.cancel_until(0);
 
-        if status == LBool::True {
-            Solution::Sat(model)
-        } else {
-            Solution::Unsat
+        match status {
+            LBool::True => {
+                self.reconstruction.extend(&mut model);
+                Solution::Sat(model)
+            }
+            LBool::False => Solution::Unsat,
+            LBool::Undef => Solution::Unknown,
         }
     }
 }
@@ -1,3 +1,5 @@
+use super::drat_clauses::ProofOutput;
+use crate::limits::Limits;
 /// Branching heuristic to be used for cdcl
 #[derive(Clone, Copy, Debug)]
 pub enum BranchingHeuristic {
@@ -12,6 +14,90 @@ pub enum BranchingHeuristic {
     Lrb,
 }
 
+/// Polarity assigned to decision variables.
+/// The default is `True`.
+#[derive(Clone, Copy, Debug)]
+pub enum Polarity {
+    /// Value the variable had when it was last unassigned (phase saving)
+    Saved,
+    /// Always true
+    True,
+    /// Always false
+    False,
+    /// Random polarity
+    Random {
+        /// Seed of the random generator
+        seed: u64,
+    },
+    /// Phase set by `Solver::set_preferred_phase`, saved phase for
+    /// variables without a preference
+    Preferred,
+}
+
+/// Restart strategy used by the cdcl search
+#[derive(Clone, Copy, Debug)]
+pub enum RestartStrategy {
+    /// Never restart
+    None,
+    /// Restart after `unit * luby(i)` conflicts in the i-th search
+    Luby {
+        /// Conflicts per Luby unit (MiniSat uses 100)
+        unit: u32,
+    },
+    /// Restart after `first * inc^i` conflicts in the i-th search
+    Geometric {
+        /// Conflicts before the first restart
+        first: f64,
+        /// Multiplier applied after every restart
+        inc: f64,
+    },
+    /// Glucose-style dynamic restarts. Restart when the fast moving average of
+    /// learnt clause LBD exceeds `margin` times the slow moving average.
+    Glucose {
+        /// Smoothing factor of the fast LBD average (e.g. 0.03)
+        fast_alpha: f64,
+        /// Smoothing factor of the slow LBD average (e.g. 1e-5)
+        slow_alpha: f64,
+        /// Restart margin (e.g. 1.25)
+        margin: f64,
+        /// Minimum number of conflicts between restarts (e.g. 50)
+        min_conflicts: u32,
+        /// Postpone the restart when the trail is larger than `block` times
+        /// its moving average (e.g. 1.4). `None` disables blocking.
+        block: Option<f64>,
+    },
+}
+
+/// Policy used to remove learnt clauses when the clause db is reduced.
+/// The default is `Activity`.
+#[derive(Clone, Copy, Debug)]
+pub enum ReductionPolicy {
+    /// Sort learnt clauses by activity and remove the less active half
+    Activity,
+    /// Keep learnt clauses in tiers by LBD (glue). Core clauses are never
+    /// removed, tier2 clauses are kept while they keep taking part in conflicts
+    /// and the worse half of the remaining local clauses is removed.
+    Tiered {
+        /// Maximum LBD of core clauses
+        core_lbd: u32,
+        /// Maximum LBD of tier2 clauses
+        tier2_lbd: u32,
+    },
+}
+
+/// Minimization of learnt clauses in conflict analysis.
+/// The default is `None`.
+#[derive(Clone, Copy, Debug)]
+pub enum LearntMinimization {
+    /// Keep the first UIP clause as is
+    None,
+    /// Remove literals whose reason only contains literals of the clause
+    Local,
+    /// Remove literals implied by the other literals of the clause
+    /// through any chain of reasons (MiniSat's recursive minimization)
+    Recursive,
+}
+
 /// Clause Db Options
 #[derive(Clone, Copy, Debug)]
 pub struct ClauseDbOptions {
@@ -19,6 +105,57 @@ pub struct ClauseDbOptions {
     pub cla_inc: f64,
     /// Clause decay
     pub cla_decay: f64,
+    /// Learnt clause reduction policy
+    pub reduction_policy: ReductionPolicy,
+}
+
+/// Preprocessing of the original clauses, at the start of each call to solve.
+/// It is not supported when the proof is in LRAT format, see `Solver::new`.
+#[derive(Clone, Copy, Debug)]
+pub struct PreprocessOptions {
+    /// Bounded variable elimination, with backward subsumption and
+    /// self-subsuming resolution. Variables frozen with `Solver::set_frozen`
+    /// and the assumptions are not eliminated.
+    pub elimination: bool,
+    /// Blocked clause elimination, on literals of the variables which may
+    /// be eliminated
+    pub blocked_clauses: bool,
+    /// Covered clause elimination, which also removes the blocked clauses
+    pub covered_clauses: bool,
+    /// Maximum number of clauses of a variable to try to eliminate it
+    pub elim_occurrence_limit: usize,
+    /// Maximum length of the resolvents of an eliminated variable
+    pub elim_resolvent_limit: usize,
+    /// Number of clauses by which eliminating a variable may increase the formula
+    pub elim_grow: usize,
+    /// Maximum number of clauses of a variable to check them for subsumption
+    pub subsumption_limit: usize,
+    /// Maximum number of literals visited by each preprocessing
+    pub effort: u64,
+}
+
+/// Inprocessing of the clauses at level 0 during search.
+/// It is not supported when the proof is in LRAT format, see `Solver::new`.
+#[derive(Clone, Copy, Debug)]
+pub struct InprocessOptions {
+    /// Number of conflicts between two inprocessing steps
+    pub interval: u64,
+    /// Failed literal probing of the literals implying others by binary
+    /// clauses, with the necessary assignments implied by both polarities
+    pub probing: bool,
+    /// Add the hyper-binary resolvents found by probing
+    pub hyper_binary_resolution: bool,
+    /// Maximum number of propagations of each probing step
+    pub probing_effort: u64,
+    /// Equivalent literal substitution, on the strongly connected components
+    /// of the binary implication graph. Variables frozen with
+    /// `Solver::set_frozen` and the assumptions are not substituted.
+    pub substitution: bool,
+    /// Vivification of the learnt clauses by increasing LBD, then of the
+    /// original clauses
+    pub vivification: bool,
+    /// Maximum number of propagations of each vivification step
+    pub vivification_effort: u64,
 }
 
 /// Solver options.
@@ -27,19 +164,70 @@ pub struct SolverOptions {
     pub clause_db_options: ClauseDbOptions,
     /// Branching Heuristic
     pub branching_heuristic: BranchingHeuristic,
+    /// Decision polarity
+    pub polarity: Polarity,
+    /// Restart strategy
+    pub restart_strategy: RestartStrategy,
+    /// Learnt clause minimization
+    pub learnt_minimization: LearntMinimization,
     /// Should capture drat clauses
     pub capture_drat: bool,
+    /// Stream the proof to a writer while solving
+    pub proof_output: Option<ProofOutput>,
+    /// Check every model against a copy of the clauses given to `add_clause`
+    pub self_check: bool,
+    /// Resource limits of each call to `solve`
+    pub limits: Limits,
+    /// Preprocessing
+    pub preprocess: PreprocessOptions,
+    /// Inprocessing
+    pub inprocess: InprocessOptions,
+}
+
+impl Default for ClauseDbOptions {
+    fn default() -> Self {
+        ClauseDbOptions {
+            cla_inc: 1.0,
+            cla_decay: 0.999,
+            reduction_policy: ReductionPolicy::Activity,
+        }
+    }
 }
 
 impl Default for SolverOptions {
     fn default() -> Self {
         SolverOptions {
-            clause_db_options: ClauseDbOptions {
-                cla_inc: 1.0,
-                cla_decay: 0.999,
-            },
+            clause_db_options: ClauseDbOptions::default(),
             branching_heuristic: BranchingHeuristic::Lrb,
+            polarity: Polarity::True,
+            restart_strategy: RestartStrategy::Geometric {
+                first: 100.0,
+                inc: 2.0,
+            },
+            learnt_minimization: LearntMinimization::None,
             capture_drat: false,
+            proof_output: None,
+            self_check: false,
+            limits: Limits::default(),
+            preprocess: PreprocessOptions {
+                elimination: false,
+                blocked_clauses: false,
+                covered_clauses: false,
+                elim_occurrence_limit: 100,
+                elim_resolvent_limit: 20,
+                elim_grow: 0,
+                subsumption_limit: 1000,
+                effort: 100_000_000,
+            },
+            inprocess: InprocessOptions {
+                interval: 5000,
+                probing: false,
+                hyper_binary_resolution: true,
+                probing_effort: 1_000_000,
+                substitution: false,
+                vivification: false,
+                vivification_effort: 1_000_000,
+            },
         }
     }
 }
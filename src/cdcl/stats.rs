@@ -0,0 +1,38 @@
+/// Statistics of the cdcl search.
+#[derive(Clone, Debug, Default)]
+pub struct SolverStats {
+    /// Number of conflicts
+    pub conflicts: u64,
+    /// Number of decisions
+    pub decisions: u64,
+    /// Number of propagated literals
+    pub propagations: u64,
+    /// Number of restarts
+    pub restarts: u64,
+    /// Number of literals in learnt clauses after minimization
+    pub learnt_literals: u64,
+    /// Number of literals removed from learnt clauses by minimization
+    pub minimized_literals: u64,
+    /// Number of variables eliminated by preprocessing
+    pub eliminated_vars: u64,
+    /// Number of clauses removed by backward subsumption
+    pub subsumed_clauses: u64,
+    /// Number of clauses strengthened by self-subsuming resolution
+    pub strengthened_clauses: u64,
+    /// Number of clauses removed by blocked clause elimination
+    pub blocked_clauses: u64,
+    /// Number of clauses removed by covered clause elimination
+    pub covered_clauses: u64,
+    /// Number of failed literals found by probing
+    pub failed_literals: u64,
+    /// Number of units implied by both polarities of a probed literal
+    pub necessary_assignments: u64,
+    /// Number of binary clauses added by hyper-binary resolution
+    pub hyper_binary_resolvents: u64,
+    /// Number of variables replaced by an equivalent literal
+    pub substituted_vars: u64,
+    /// Number of clauses shortened by vivification
+    pub vivified_clauses: u64,
+    /// Number of literals removed by vivification
+    pub vivified_literals: u64,
+}
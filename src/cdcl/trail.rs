@@ -38,6 +38,19 @@ impl Trail {
         self.trail.len()
     }
 
+    pub fn lit(&self, i: usize) -> Lit {
+        self.trail[i]
+    }
+
+    /// Position in the trail of the first literal assigned at `level`.
+    pub fn level_start(&self, level: i32) -> usize {
+        if level == 0 {
+            0
+        } else {
+            self.trail_lim[level as usize - 1] as usize
+        }
+    }
+
     pub fn trail_lim_pop(&mut self) -> Option<i32> {
         self.trail_lim.pop()
     }
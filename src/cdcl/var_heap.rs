@@ -0,0 +1,176 @@
+use solhop_types::Var;
+
+/// Binary max-heap of variables ordered by an external key
+/// (activity for VSIDS, ema for LRB).
+///
+/// The keys are not owned by the heap, so every operation that
+/// compares variables takes the key slice as argument.
+pub struct VarHeap {
+    heap: Vec<Var>,
+    indices: Vec<Option<usize>>,
+}
+
+impl VarHeap {
+    pub fn new() -> Self {
+        VarHeap {
+            heap: vec![],
+            indices: vec![],
+        }
+    }
+
+    /// Make room for a new variable. The variable is not inserted.
+    pub fn grow(&mut self) {
+        self.indices.push(None);
+    }
+
+    pub fn contains(&self, v: Var) -> bool {
+        self.indices[v.index()].is_some()
+    }
+
+    /// Returns the variable with the highest key without removing it.
+    pub fn top(&self) -> Option<Var> {
+        self.heap.first().copied()
+    }
+
+    pub fn insert(&mut self, v: Var, keys: &[f64]) {
+        if self.contains(v) {
+            return;
+        }
+        self.indices[v.index()] = Some(self.heap.len());
+        self.heap.push(v);
+        self.percolate_up(self.heap.len() - 1, keys);
+    }
+
+    pub fn remove(&mut self, v: Var, keys: &[f64]) {
+        if let Some(i) = self.indices[v.index()] {
+            self.indices[v.index()] = None;
+            let last = self.heap.pop().unwrap();
+            if i < self.heap.len() {
+                self.heap[i] = last;
+                self.indices[last.index()] = Some(i);
+                self.percolate_up(i, keys);
+                self.percolate_down(self.indices[last.index()].unwrap(), keys);
+            }
+        }
+    }
+
+    /// Restore the heap property after the key of `v` was increased.
+    pub fn increase(&mut self, v: Var, keys: &[f64]) {
+        if let Some(i) = self.indices[v.index()] {
+            self.percolate_up(i, keys);
+        }
+    }
+
+    fn percolate_up(&mut self, mut i: usize, keys: &[f64]) {
+        let v = self.heap[i];
+        while i > 0 {
+            let parent = (i - 1) / 2;
+            if keys[self.heap[parent].index()] >= keys[v.index()] {
+                break;
+            }
+            self.heap[i] = self.heap[parent];
+            self.indices[self.heap[i].index()] = Some(i);
+            i = parent;
+        }
+        self.heap[i] = v;
+        self.indices[v.index()] = Some(i);
+    }
+
+    fn percolate_down(&mut self, mut i: usize, keys: &[f64]) {
+        let v = self.heap[i];
+        loop {
+            let left = 2 * i + 1;
+            if left >= self.heap.len() {
+                break;
+            }
+            let right = left + 1;
+            let child = if right < self.heap.len()
+                && keys[self.heap[right].index()] > keys[self.heap[left].index()]
+            {
+                right
+            } else {
+                left
+            };
+            if keys[self.heap[child].index()] <= keys[v.index()] {
+                break;
+            }
+            self.heap[i] = self.heap[child];
+            self.indices[self.heap[i].index()] = Some(i);
+            i = child;
+        }
+        self.heap[i] = v;
+        self.indices[v.index()] = Some(i);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn heap(keys: &[f64]) -> VarHeap {
+        let mut heap = VarHeap::new();
+        for v in 0..keys.len() {
+            heap.grow();
+            heap.insert(Var::new(v), keys);
+        }
+        heap
+    }
+
+    /// Remove all the variables from the top, returning their indices.
+    fn drain(heap: &mut VarHeap, keys: &[f64]) -> Vec<usize> {
+        let mut order = vec![];
+        while let Some(v) = heap.top() {
+            heap.remove(v, keys);
+            order.push(v.index());
+        }
+        order
+    }
+
+    #[test]
+    fn top_has_the_highest_key() {
+        let keys = [3.0, 1.0, 4.0, 1.5, 5.0, 9.0, 2.0, 6.0];
+        let mut heap = heap(&keys);
+        assert_eq!(drain(&mut heap, &keys), vec![5, 7, 4, 2, 0, 6, 3, 1]);
+        assert_eq!(heap.top(), None);
+    }
+
+    #[test]
+    fn remove_keeps_the_order() {
+        let keys = [3.0, 1.0, 4.0, 1.5, 5.0, 9.0, 2.0, 6.0];
+        let mut heap = heap(&keys);
+        heap.remove(Var::new(4), &keys);
+        heap.remove(Var::new(1), &keys);
+        // Removing a variable which is not in the heap does nothing
+        heap.remove(Var::new(4), &keys);
+        assert!(!heap.contains(Var::new(4)));
+        assert!(heap.contains(Var::new(0)));
+        assert_eq!(drain(&mut heap, &keys), vec![5, 7, 2, 0, 6, 3]);
+    }
+
+    #[test]
+    fn increase_moves_the_variable_up() {
+        let mut keys = vec![3.0, 1.0, 4.0, 1.5, 5.0];
+        let mut heap = heap(&keys);
+        keys[1] = 10.0;
+        heap.increase(Var::new(1), &keys);
+        assert_eq!(heap.top(), Some(Var::new(1)));
+        keys[3] = 4.5;
+        heap.increase(Var::new(3), &keys);
+        assert_eq!(drain(&mut heap, &keys), vec![1, 4, 3, 2, 0]);
+    }
+
+    #[test]
+    fn reinsert_after_remove_uses_the_new_key() {
+        let mut keys = vec![3.0, 1.0, 4.0, 1.5, 5.0];
+        let mut heap = heap(&keys);
+        heap.remove(Var::new(4), &keys);
+        heap.remove(Var::new(1), &keys);
+        keys[4] = 0.5;
+        keys[1] = 3.5;
+        heap.insert(Var::new(4), &keys);
+        heap.insert(Var::new(1), &keys);
+        // Inserting a variable twice does nothing
+        heap.insert(Var::new(1), &keys);
+        assert_eq!(drain(&mut heap, &keys), vec![2, 1, 0, 3, 4]);
+    }
+}
@@ -1,5 +1,7 @@
-use super::solver_options::BranchingHeuristic;
-use crate::cdcl::clause_db::ClauseIndex;
+use super::solver_options::{BranchingHeuristic, Polarity};
+use super::var_heap::VarHeap;
+use crate::cdcl::clause_db::ClauseRef;
+use rand::prelude::*;
 use solhop_types::{LBool, Lit, Var};
 
 enum InternalBranchStats {
@@ -11,7 +13,10 @@ enum InternalBranchStats {
     Lrb {
         alpha: f64,
         learnt_counter: usize,
+        /// Ema of assigned variables, and ema divided by `ema_scale`
+        /// for unassigned variables so that decaying all of them is O(1).
         ema: Vec<f64>,
+        ema_scale: f64,
         assigned: Vec<usize>,
         participated: Vec<usize>,
         reasoned: Vec<usize>,
@@ -20,13 +25,28 @@ enum InternalBranchStats {
 
 pub struct VarManager {
     assigns: Vec<LBool>,
-    reason: Vec<Option<ClauseIndex>>,
+    reason: Vec<Option<ClauseRef>>,
     level: Vec<i32>,
     stats: InternalBranchStats,
+    /// Unassigned variables ordered by activity/ema.
+    order_heap: VarHeap,
+    polarity: Polarity,
+    saved_phase: Vec<bool>,
+    preferred_phase: Vec<Option<bool>>,
+    /// Whether each variable can be selected as decision,
+    /// i.e. it is not removed by preprocessing
+    decision: Vec<bool>,
+    rng: StdRng,
+    lbd_seen: Vec<u64>,
+    lbd_stamp: u64,
 }
 
 impl VarManager {
-    pub fn new(bh: BranchingHeuristic) -> Self {
+    pub fn new(bh: BranchingHeuristic, polarity: Polarity) -> Self {
+        let seed = match polarity {
+            Polarity::Random { seed } => seed,
+            _ => 0,
+        };
         VarManager {
             assigns: vec![],
             reason: vec![],
@@ -41,11 +61,20 @@ impl VarManager {
                     alpha: 0.4,
                     learnt_counter: 0,
                     ema: vec![],
+                    ema_scale: 1.0,
                     assigned: vec![],
                     participated: vec![],
                     reasoned: vec![],
                 },
             },
+            order_heap: VarHeap::new(),
+            polarity,
+            saved_phase: vec![],
+            preferred_phase: vec![],
+            decision: vec![],
+            rng: StdRng::seed_from_u64(seed),
+            lbd_seen: vec![],
+            lbd_stamp: 0,
         }
     }
 
@@ -58,6 +87,9 @@ impl VarManager {
         self.reason.push(None);
         self.assigns.push(LBool::Undef);
         self.level.push(-1);
+        self.saved_phase.push(false);
+        self.preferred_phase.push(None);
+        self.decision.push(true);
         match &mut self.stats {
             InternalBranchStats::Vsids { activity, .. } => {
                 activity.push(0.0);
@@ -75,6 +107,12 @@ impl VarManager {
                 reasoned.push(0);
             }
         }
+        self.order_heap.grow();
+        let keys = match &self.stats {
+            InternalBranchStats::Vsids { activity, .. } => activity,
+            InternalBranchStats::Lrb { ema, .. } => ema,
+        };
+        self.order_heap.insert(v, keys);
         v
     }
 
@@ -101,6 +139,7 @@ impl VarManager {
                 alpha,
                 learnt_counter,
                 ema,
+                ema_scale,
                 participated,
                 reasoned,
                 ..
@@ -115,27 +154,64 @@ impl VarManager {
                 for v in reasoned_variables {
                     reasoned[v.index()] += 1;
                 }
-                for (index, value) in ema.iter_mut().enumerate() {
-                    if self.assigns[index] == LBool::Undef {
-                        *value *= 0.95;
+                // Decay ema of all unassigned variables. Uniform scaling
+                // keeps the order heap valid.
+                *ema_scale *= 0.95;
+                if *ema_scale < 1e-100 {
+                    for (index, value) in ema.iter_mut().enumerate() {
+                        if self.assigns[index] == LBool::Undef {
+                            *value *= *ema_scale;
+                        }
                     }
+                    *ema_scale = 1.0;
                 }
             }
         }
     }
 
-    pub fn select_var(&self) -> Var {
-        let max_v = match &self.stats {
-            InternalBranchStats::Vsids { activity, .. } => (0..self.n_vars())
-                .filter(|v| self.value(Var::new(*v)) == LBool::Undef)
-                .max_by(|&x, &y| activity[x].partial_cmp(&activity[y]).unwrap())
-                .unwrap(),
-            InternalBranchStats::Lrb { ema, .. } => (0..self.n_vars())
-                .filter(|v| self.value(Var::new(*v)) == LBool::Undef)
-                .max_by(|&x, &y| ema[x].partial_cmp(&ema[y]).unwrap())
-                .unwrap(),
+    /// Returns the unassigned decision variable with highest activity/ema,
+    /// `None` if all decision variables are assigned.
+    /// The variable leaves the order heap once it is assigned.
+    pub fn select_var(&self) -> Option<Var> {
+        self.order_heap.top()
+    }
+
+    /// Returns the decision literal for the next branching variable,
+    /// `None` if all decision variables are assigned.
+    pub fn select_lit(&mut self) -> Option<Lit> {
+        let v = self.select_var()?;
+        let value = match self.polarity {
+            Polarity::Saved => self.saved_phase[v.index()],
+            Polarity::True => true,
+            Polarity::False => false,
+            Polarity::Random { .. } => self.rng.gen(),
+            Polarity::Preferred => {
+                self.preferred_phase[v.index()].unwrap_or(self.saved_phase[v.index()])
+            }
+        };
+        Some(Lit::new(v, !value))
+    }
+
+    pub fn is_decision(&self, var: Var) -> bool {
+        self.decision[var.index()]
+    }
+
+    /// Allow or prevent the selection of the variable as decision.
+    pub fn set_decision(&mut self, var: Var, decision: bool) {
+        self.decision[var.index()] = decision;
+        let keys = match &self.stats {
+            InternalBranchStats::Vsids { activity, .. } => activity,
+            InternalBranchStats::Lrb { ema, .. } => ema,
         };
-        Var::new(max_v)
+        if !decision && self.order_heap.contains(var) {
+            self.order_heap.remove(var, keys);
+        } else if decision && self.assigns[var.index()] == LBool::Undef {
+            self.order_heap.insert(var, keys);
+        }
+    }
+
+    pub fn set_preferred_phase(&mut self, var: Var, phase: Option<bool>) {
+        self.preferred_phase[var.index()] = phase;
     }
 
     pub fn after_learnt_clause(&mut self, ps: &[Lit]) {
@@ -153,6 +229,7 @@ impl VarManager {
                         }
                         *var_inc *= 1e-100;
                     }
+                    self.order_heap.increase(x, activity);
                 }
             }
             InternalBranchStats::Lrb { .. } => {}
@@ -171,22 +248,34 @@ impl VarManager {
         }
     }
 
-    pub fn get_reason(&self, var: Var) -> Option<ClauseIndex> {
+    pub fn get_reason(&self, var: Var) -> Option<ClauseRef> {
         self.reason[var.index()]
     }
 
-    pub fn update(&mut self, var: Var, value: LBool, level: i32, reason: Option<ClauseIndex>) {
+    pub fn update(&mut self, var: Var, value: LBool, level: i32, reason: Option<ClauseRef>) {
+        if value == LBool::Undef && self.assigns[var.index()] != LBool::Undef {
+            self.saved_phase[var.index()] = self.assigns[var.index()] == LBool::True;
+        }
         match &mut self.stats {
-            InternalBranchStats::Vsids { .. } => {}
+            InternalBranchStats::Vsids { activity, .. } => {
+                if value != LBool::Undef {
+                    self.order_heap.remove(var, activity);
+                } else if self.decision[var.index()] {
+                    self.order_heap.insert(var, activity);
+                }
+            }
             InternalBranchStats::Lrb {
                 alpha,
                 learnt_counter,
                 ema,
+                ema_scale,
                 assigned,
                 participated,
                 reasoned,
             } => {
                 if value != LBool::Undef {
+                    self.order_heap.remove(var, ema);
+                    ema[var.index()] *= *ema_scale;
                     assigned[var.index()] = *learnt_counter;
                     participated[var.index()] = 0;
                     reasoned[var.index()] = 0;
@@ -200,6 +289,10 @@ impl VarManager {
                         let next_ema = (1.0 - *alpha) * prev_ema + *alpha * (r + rsr);
                         ema[var.index()] = next_ema;
                     }
+                    ema[var.index()] /= *ema_scale;
+                    if self.decision[var.index()] {
+                        self.order_heap.insert(var, ema);
+                    }
                 }
             }
         }
@@ -209,6 +302,16 @@ impl VarManager {
         self.reason[var.index()] = reason;
     }
 
+    /// Update reasons after clause db garbage collection.
+    /// Reasons that were deleted are removed.
+    pub fn relocate_reasons(&mut self, forward: impl Fn(ClauseRef) -> Option<ClauseRef>) {
+        for reason in self.reason.iter_mut() {
+            if let Some(cr) = *reason {
+                *reason = forward(cr);
+            }
+        }
+    }
+
     pub fn reset(&mut self, var: Var) {
         self.update(var, LBool::Undef, -1, None);
     }
@@ -220,4 +323,26 @@ impl VarManager {
     pub fn get_level(&self, var: Var) -> i32 {
         self.level[var.index()]
     }
+
+    /// Number of distinct decision levels among the assigned literals
+    /// (literal block distance).
+    pub fn compute_lbd(&mut self, lits: impl IntoIterator<Item = Lit>) -> u32 {
+        self.lbd_stamp += 1;
+        let mut lbd = 0;
+        for lit in lits {
+            let level = self.level[lit.var().index()];
+            if level < 0 {
+                continue;
+            }
+            let level = level as usize;
+            if level >= self.lbd_seen.len() {
+                self.lbd_seen.resize(level + 1, 0);
+            }
+            if self.lbd_seen[level] != self.lbd_stamp {
+                self.lbd_seen[level] = self.lbd_stamp;
+                lbd += 1;
+            }
+        }
+        lbd
+    }
 }
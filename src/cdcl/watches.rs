@@ -0,0 +1,97 @@
+use super::clause_db::ClauseRef;
+use solhop_types::Lit;
+
+/// Entry of a watch list. If the blocker literal is true, the clause is
+/// satisfied and does not need to be visited. For binary clauses the
+/// blocker is the other literal of the clause.
+#[derive(Clone, Copy, Debug)]
+pub struct Watcher {
+    pub cref: ClauseRef,
+    pub blocker: Lit,
+}
+
+/// Watch lists indexed by literal. The list of `p` holds the clauses
+/// watching `!p`, i.e. the clauses to visit when `p` becomes true.
+pub struct Watches {
+    long: Vec<Vec<Watcher>>,
+    binary: Vec<Vec<Watcher>>,
+}
+
+impl Watches {
+    pub fn new() -> Self {
+        Watches {
+            long: vec![],
+            binary: vec![],
+        }
+    }
+
+    pub fn new_var(&mut self) {
+        for _ in 0..2 {
+            self.long.push(vec![]);
+            self.binary.push(vec![]);
+        }
+    }
+
+    /// Watch the first two literals of the clause.
+    pub fn attach(&mut self, cref: ClauseRef, lit_0: Lit, lit_1: Lit, binary: bool) {
+        let lists = if binary {
+            &mut self.binary
+        } else {
+            &mut self.long
+        };
+        lists[(!lit_0).index()].push(Watcher {
+            cref,
+            blocker: lit_1,
+        });
+        lists[(!lit_1).index()].push(Watcher {
+            cref,
+            blocker: lit_0,
+        });
+    }
+
+    pub fn detach(&mut self, cref: ClauseRef, lit_0: Lit, lit_1: Lit, binary: bool) {
+        let lists = if binary {
+            &mut self.binary
+        } else {
+            &mut self.long
+        };
+        for &lit in [lit_0, lit_1].iter() {
+            let list = &mut lists[(!lit).index()];
+            if let Some(pos) = list.iter().position(|w| w.cref == cref) {
+                list.remove(pos);
+            }
+        }
+    }
+
+    pub fn binary(&self, p: Lit) -> &[Watcher] {
+        &self.binary[p.index()]
+    }
+
+    /// Take the long clause watch list of `p`, to be given back with `put_long`.
+    pub fn take_long(&mut self, p: Lit) -> Vec<Watcher> {
+        std::mem::take(&mut self.long[p.index()])
+    }
+
+    pub fn put_long(&mut self, p: Lit, list: Vec<Watcher>) {
+        debug_assert!(self.long[p.index()].is_empty());
+        self.long[p.index()] = list;
+    }
+
+    pub fn push_long(&mut self, p: Lit, watcher: Watcher) {
+        self.long[p.index()].push(watcher);
+    }
+
+    /// Update clause references after clause db garbage collection, and
+    /// drop the watchers of deleted clauses.
+    pub fn relocate(&mut self, forward: impl Fn(ClauseRef) -> Option<ClauseRef>) {
+        for list in self.long.iter_mut().chain(self.binary.iter_mut()) {
+            list.retain_mut(|w| match forward(w.cref) {
+                Some(cref) => {
+                    w.cref = cref;
+                    true
+                }
+                None => false,
+            });
+        }
+    }
+}
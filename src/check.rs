@@ -0,0 +1,86 @@
+use solhop_types::{Clause, Lit};
+use std::fmt;
+
+/// Internal error of a solver, found by checking its answer.
+#[derive(Clone, Debug, PartialEq)]
+pub enum InternalError {
+    /// The model does not satisfy the clause at this index,
+    /// in the order in which the clauses were added.
+    UnsatisfiedClause(usize),
+    /// The model does not satisfy the assumption at this index.
+    UnsatisfiedAssumption(usize),
+}
+
+impl fmt::Display for InternalError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            InternalError::UnsatisfiedClause(i) => {
+                write!(f, "model does not satisfy clause {}", i)
+            }
+            InternalError::UnsatisfiedAssumption(i) => {
+                write!(f, "model does not satisfy assumption {}", i)
+            }
+        }
+    }
+}
+
+impl std::error::Error for InternalError {}
+
+/// Whether the literal is true in the model. Variables missing from the model are false.
+fn is_true(model: &[bool], lit: Lit) -> bool {
+    model.get(lit.var().index()).copied().unwrap_or(false) != lit.sign()
+}
+
+/// Check that the model satisfies the assumptions and the clauses.
+pub fn check_model(
+    clauses: &[Clause],
+    assumps: &[Lit],
+    model: &[bool],
+) -> Result<(), InternalError> {
+    if let Some(i) = assumps.iter().position(|&lit| !is_true(model, lit)) {
+        return Err(InternalError::UnsatisfiedAssumption(i));
+    }
+    match clauses
+        .iter()
+        .position(|clause| !clause.lits.iter().any(|&lit| is_true(model, lit)))
+    {
+        Some(i) => Err(InternalError::UnsatisfiedClause(i)),
+        None => Ok(()),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use solhop_types::Var;
+
+    fn clause(lits: &[Lit]) -> Clause {
+        Clause {
+            lits: lits.to_vec(),
+        }
+    }
+
+    #[test]
+    fn bad_models_are_rejected() {
+        let (x, y) = (Var::new(0), Var::new(1));
+        let clauses = vec![
+            clause(&[x.pos_lit(), y.pos_lit()]),
+            clause(&[x.neg_lit(), y.pos_lit()]),
+        ];
+        let assumps = [y.pos_lit(), x.neg_lit()];
+        assert_eq!(check_model(&clauses, &assumps, &[false, true]), Ok(()));
+        assert_eq!(
+            check_model(&clauses, &[], &[true, false]),
+            Err(InternalError::UnsatisfiedClause(1))
+        );
+        assert_eq!(
+            check_model(&clauses, &assumps, &[true, true]),
+            Err(InternalError::UnsatisfiedAssumption(1))
+        );
+        // Missing variables are false
+        assert_eq!(
+            check_model(&clauses, &[], &[]),
+            Err(InternalError::UnsatisfiedClause(0))
+        );
+    }
+}
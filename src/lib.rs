@@ -18,6 +18,7 @@
 //! assert_eq!(solver.solve(vec![]), Solution::Sat(vec![true, false, true]));
 //!
 //! assert_eq!(solver.solve(vec![vars[2].neg_lit()]), Solution::Unsat);
+//! assert_eq!(solver.failed_assumptions(), &[vars[2].neg_lit()]);
 //!
 //! assert_eq!(solver.solve(vec![]), Solution::Sat(vec![true, false, true]));
 //!
@@ -30,3 +31,12 @@ pub mod sls;
 
 /// CDCL solver module.
 pub mod cdcl;
+
+/// Model checking module.
+pub mod check;
+
+/// DRAT and LRAT proof checking module.
+pub mod proof;
+
+/// Resource limits of the solvers.
+pub mod limits;
@@ -0,0 +1,76 @@
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+
+/// Resource limits of each call to a solver. The cdcl solver returns
+/// `Solution::Unknown` when one of them is reached, the local search solver
+/// returns `Solution::Best` with the best assignment found so far.
+#[derive(Clone, Debug, Default)]
+pub struct Limits {
+    /// Maximum wall-clock time, checked every 1024 decisions or flips
+    pub time: Option<Duration>,
+    /// Maximum number of conflicts, for the cdcl solver
+    pub conflicts: Option<u64>,
+    /// Maximum number of propagated literals, for the cdcl solver
+    pub propagations: Option<u64>,
+    /// Maximum number of flips over all tries, for the local search solver
+    pub flips: Option<u64>,
+    /// Flag which stops the search when set, e.g. from another thread.
+    /// It is not cleared by the solvers.
+    pub interrupt: Option<Arc<AtomicBool>>,
+}
+
+/// Number of calls to `Limits::reached` between two reads of the clock.
+const CLOCK_INTERVAL: u64 = 1024;
+
+/// Resources used since the start of a call to a solver.
+pub(crate) struct Usage {
+    pub start: Instant,
+    pub conflicts: u64,
+    pub propagations: u64,
+    pub flips: u64,
+    /// Number of calls to `Limits::reached`
+    pub checks: u64,
+    /// Whether the time limit was found reached
+    pub timed_out: bool,
+}
+
+impl Usage {
+    pub fn new() -> Self {
+        Usage {
+            start: Instant::now(),
+            conflicts: 0,
+            propagations: 0,
+            flips: 0,
+            checks: 0,
+            timed_out: false,
+        }
+    }
+}
+
+impl Limits {
+    /// Whether one of the limits is reached. The clock is only read every
+    /// `CLOCK_INTERVAL` calls, as the solvers check the limits at each
+    /// decision or flip.
+    pub(crate) fn reached(&self, usage: &mut Usage) -> bool {
+        usage.checks += 1;
+        if let Some(time) = self.time {
+            if usage.checks.is_multiple_of(CLOCK_INTERVAL) {
+                usage.timed_out = usage.start.elapsed() >= time;
+            }
+        }
+        let exceeds = |limit: Option<u64>, used: u64| match limit {
+            Some(limit) => used >= limit,
+            None => false,
+        };
+        let interrupted = match &self.interrupt {
+            Some(interrupt) => interrupt.load(Ordering::Relaxed),
+            None => false,
+        };
+        interrupted
+            || exceeds(self.conflicts, usage.conflicts)
+            || exceeds(self.propagations, usage.propagations)
+            || exceeds(self.flips, usage.flips)
+            || usage.timed_out
+    }
+}
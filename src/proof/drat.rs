@@ -0,0 +1,247 @@
+use super::ProofError;
+use crate::cdcl::DratClause;
+use solhop_types::{Clause, Lit};
+use std::collections::HashMap;
+
+/// Check a DRAT proof of unsatisfiability of the formula.
+///
+/// The proof is checked forwards: every added clause must be a reverse unit
+/// propagation (RUP) consequence of the clauses before it, or have the
+/// resolution asymmetric tautology (RAT) property on its first literal.
+/// As done by drat-trim, deletions of unit clauses and of reasons of literals
+/// assigned at the top level are ignored.
+pub fn check_drat(formula: &[Clause], proof: &[DratClause]) -> Result<(), ProofError> {
+    let mut checker = Checker::new();
+    for clause in formula {
+        checker.add(&clause.lits);
+    }
+    for (i, step) in proof.iter().enumerate() {
+        match step {
+            DratClause::Add(lits) => {
+                if !checker.is_rup(lits, &[]) && !checker.is_rat(lits) {
+                    return Err(ProofError::InvalidStep(i));
+                }
+                if lits.is_empty() {
+                    return Ok(());
+                }
+                checker.add(lits);
+            }
+            DratClause::Delete(lits) => checker.delete(lits),
+        }
+    }
+    if checker.inconsistent {
+        Ok(())
+    } else {
+        Err(ProofError::NoEmptyClause)
+    }
+}
+
+/// Sorted literal indices of a clause, without duplicates.
+fn key(lits: &[Lit]) -> Vec<usize> {
+    let mut key: Vec<usize> = lits.iter().map(|lit| lit.index()).collect();
+    key.sort_unstable();
+    key.dedup();
+    key
+}
+
+struct Checker {
+    clauses: Vec<Vec<Lit>>,
+    deleted: Vec<bool>,
+    /// Clauses by key, to find the clauses to delete
+    by_key: HashMap<Vec<usize>, Vec<usize>>,
+    /// Watches of `p` hold the clauses watching `p`, visited when `p` becomes false
+    watches: Vec<Vec<usize>>,
+    /// Whether each literal is true
+    assigned: Vec<bool>,
+    trail: Vec<Lit>,
+    head: usize,
+    /// Unit propagation on the clauses gives a conflict
+    inconsistent: bool,
+}
+
+impl Checker {
+    fn new() -> Self {
+        Checker {
+            clauses: vec![],
+            deleted: vec![],
+            by_key: HashMap::new(),
+            watches: vec![],
+            assigned: vec![],
+            trail: vec![],
+            head: 0,
+            inconsistent: false,
+        }
+    }
+
+    fn grow(&mut self, lits: &[Lit]) {
+        if let Some(max) = lits.iter().map(|lit| lit.var().index()).max() {
+            let len = 2 * (max + 1);
+            if self.assigned.len() < len {
+                self.assigned.resize(len, false);
+                self.watches.resize(len, vec![]);
+            }
+        }
+    }
+
+    fn is_true(&self, lit: Lit) -> bool {
+        self.assigned[lit.index()]
+    }
+
+    fn is_false(&self, lit: Lit) -> bool {
+        self.assigned[(!lit).index()]
+    }
+
+    fn assign(&mut self, lit: Lit) {
+        self.assigned[lit.index()] = true;
+        self.trail.push(lit);
+    }
+
+    fn backtrack(&mut self, len: usize) {
+        for lit in self.trail.drain(len..) {
+            self.assigned[lit.index()] = false;
+        }
+        self.head = len;
+    }
+
+    /// Propagate the assigned literals, returns true on conflict.
+    fn propagate(&mut self) -> bool {
+        while self.head < self.trail.len() {
+            let false_lit = !self.trail[self.head];
+            self.head += 1;
+            let mut ws = std::mem::take(&mut self.watches[false_lit.index()]);
+            let mut conflict = false;
+            let mut i = 0;
+            'watches: while i < ws.len() {
+                let ci = ws[i];
+                if self.deleted[ci] {
+                    ws.swap_remove(i);
+                    continue;
+                }
+                let clause = &mut self.clauses[ci];
+                if clause[0] == false_lit {
+                    clause.swap(0, 1);
+                }
+                let first = clause[0];
+                if self.assigned[first.index()] {
+                    i += 1;
+                    continue;
+                }
+                for k in 2..clause.len() {
+                    if !self.assigned[(!clause[k]).index()] {
+                        clause.swap(1, k);
+                        self.watches[clause[1].index()].push(ci);
+                        ws.swap_remove(i);
+                        continue 'watches;
+                    }
+                }
+                i += 1;
+                if self.is_false(first) {
+                    conflict = true;
+                    break;
+                }
+                self.assign(first);
+            }
+            self.watches[false_lit.index()] = ws;
+            if conflict {
+                return true;
+            }
+        }
+        false
+    }
+
+    fn add(&mut self, lits: &[Lit]) {
+        self.grow(lits);
+        let mut clause = lits.to_vec();
+        clause.sort_by_key(|lit| lit.index());
+        clause.dedup();
+        // Literals not false first
+        clause.sort_by_key(|&lit| self.is_false(lit));
+
+        let ci = self.clauses.len();
+        self.by_key.entry(key(&clause)).or_default().push(ci);
+        if clause.len() >= 2 {
+            self.watches[clause[0].index()].push(ci);
+            self.watches[clause[1].index()].push(ci);
+        }
+        if !self.inconsistent && (clause.len() < 2 || self.is_false(clause[1])) {
+            // Unit or falsified under the top level assignment
+            match clause.first() {
+                None => self.inconsistent = true,
+                Some(&lit) if self.is_false(lit) => self.inconsistent = true,
+                Some(&lit) if !self.is_true(lit) => {
+                    self.assign(lit);
+                    self.inconsistent = self.propagate();
+                }
+                _ => {}
+            }
+        }
+        self.clauses.push(clause);
+        self.deleted.push(false);
+    }
+
+    fn delete(&mut self, lits: &[Lit]) {
+        self.grow(lits);
+        let key = key(lits);
+        let ci = match self.by_key.get(&key).and_then(|cis| cis.last()) {
+            Some(&ci) => ci,
+            None => return,
+        };
+        let clause = &self.clauses[ci];
+        let is_reason = clause.iter().filter(|&&lit| !self.is_false(lit)).count() == 1
+            && clause.iter().any(|&lit| self.is_true(lit));
+        if clause.len() < 2 || is_reason {
+            return;
+        }
+        self.deleted[ci] = true;
+        let cis = self.by_key.get_mut(&key).unwrap();
+        cis.pop();
+        if cis.is_empty() {
+            self.by_key.remove(&key);
+        }
+    }
+
+    /// Whether assigning the negation of the literals of both clauses gives
+    /// a conflict by unit propagation.
+    fn is_rup(&mut self, lits: &[Lit], other: &[Lit]) -> bool {
+        if self.inconsistent {
+            return true;
+        }
+        self.grow(lits);
+        self.grow(other);
+        let top = self.trail.len();
+        let mut conflict = false;
+        for &lit in lits.iter().chain(other.iter()) {
+            if self.is_true(lit) {
+                conflict = true;
+                break;
+            } else if !self.is_false(lit) {
+                self.assign(!lit);
+            }
+        }
+        let conflict = conflict || self.propagate();
+        self.backtrack(top);
+        conflict
+    }
+
+    /// Whether every resolvent of the clause on its first literal is RUP.
+    fn is_rat(&mut self, lits: &[Lit]) -> bool {
+        let pivot = match lits.first() {
+            Some(&lit) => lit,
+            None => return false,
+        };
+        for ci in 0..self.clauses.len() {
+            if self.deleted[ci] || !self.clauses[ci].contains(&!pivot) {
+                continue;
+            }
+            let other: Vec<Lit> = self.clauses[ci]
+                .iter()
+                .copied()
+                .filter(|&lit| lit != !pivot)
+                .collect();
+            if !self.is_rup(lits, &other) {
+                return false;
+            }
+        }
+        true
+    }
+}
@@ -0,0 +1,119 @@
+use super::ProofError;
+use solhop_types::{Clause, Lit};
+use std::collections::HashMap;
+
+/// Step of an LRAT proof.
+#[derive(Clone, Debug, PartialEq)]
+pub enum LratStep {
+    /// Clause with the given id, implied by unit propagation on the hint clauses,
+    /// each of them being unit or falsified in turn.
+    Add {
+        /// Id of the clause
+        id: u64,
+        /// Literals of the clause
+        lits: Vec<Lit>,
+        /// Ids of the antecedents of the clause
+        hints: Vec<u64>,
+    },
+    /// Deletion of the clauses with the given ids.
+    Delete(Vec<u64>),
+}
+
+/// Check an LRAT proof of unsatisfiability of the formula, whose clauses
+/// have ids starting from 1 in order.
+///
+/// Each added clause is checked by replaying its hints, which is linear
+/// in the size of the hint clauses.
+pub fn check_lrat(formula: &[Clause], proof: &[LratStep]) -> Result<(), ProofError> {
+    let mut clauses: HashMap<u64, Vec<Lit>> = formula
+        .iter()
+        .enumerate()
+        .map(|(i, clause)| (i as u64 + 1, clause.lits.clone()))
+        .collect();
+    let mut assigned: Vec<bool> = vec![];
+
+    for (i, step) in proof.iter().enumerate() {
+        match step {
+            LratStep::Add { id, lits, hints } => {
+                if clauses.contains_key(id) || !replay_hints(&clauses, &mut assigned, lits, hints) {
+                    return Err(ProofError::InvalidStep(i));
+                }
+                if lits.is_empty() {
+                    return Ok(());
+                }
+                clauses.insert(*id, lits.clone());
+            }
+            LratStep::Delete(ids) => {
+                for id in ids {
+                    clauses.remove(id);
+                }
+            }
+        }
+    }
+    Err(ProofError::NoEmptyClause)
+}
+
+/// Assign the negation of the clause, then check that each hint is unit,
+/// assigning its last literal, until one of them is falsified.
+fn replay_hints(
+    clauses: &HashMap<u64, Vec<Lit>>,
+    assigned: &mut Vec<bool>,
+    lits: &[Lit],
+    hints: &[u64],
+) -> bool {
+    let mut trail = vec![];
+    let is_true = |assigned: &Vec<bool>, lit: Lit| assigned.get(lit.index()) == Some(&true);
+
+    let mut tautology = false;
+    for &lit in lits {
+        tautology |= is_true(assigned, lit);
+        assign(assigned, &mut trail, !lit);
+    }
+
+    let mut conflict = tautology;
+    for hint in hints {
+        if conflict {
+            break;
+        }
+        let clause = match clauses.get(hint) {
+            Some(clause) => clause,
+            None => break,
+        };
+        let mut unassigned = None;
+        let mut unit = true;
+        for &lit in clause {
+            if is_true(assigned, lit) {
+                unit = false;
+                break;
+            } else if !is_true(assigned, !lit) && unassigned != Some(lit) {
+                if unassigned.is_some() {
+                    unit = false;
+                    break;
+                }
+                unassigned = Some(lit);
+            }
+        }
+        if !unit {
+            break;
+        }
+        match unassigned {
+            None => conflict = true,
+            Some(lit) => assign(assigned, &mut trail, lit),
+        }
+    }
+
+    for lit in trail {
+        assigned[lit.index()] = false;
+    }
+    conflict
+}
+
+fn assign(assigned: &mut Vec<bool>, trail: &mut Vec<Lit>, lit: Lit) {
+    if assigned.len() <= lit.index() {
+        assigned.resize(2 * (lit.var().index() + 1), false);
+    }
+    if !assigned[lit.index()] {
+        assigned[lit.index()] = true;
+        trail.push(lit);
+    }
+}
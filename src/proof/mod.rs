@@ -0,0 +1,16 @@
+mod drat;
+mod lrat;
+mod parse;
+
+pub use drat::check_drat;
+pub use lrat::{check_lrat, LratStep};
+pub use parse::{read_drat, read_lrat};
+
+/// Reason for which a proof is rejected.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum ProofError {
+    /// The step at this index of the proof is not implied by the clauses before it.
+    InvalidStep(usize),
+    /// The proof is valid but does not derive the empty clause.
+    NoEmptyClause,
+}
@@ -0,0 +1,145 @@
+use super::LratStep;
+use crate::cdcl::{DratClause, ProofFormat};
+use solhop_types::{Lit, Var};
+use std::io::{self, BufRead, Read};
+
+fn invalid(msg: &str) -> io::Error {
+    io::Error::new(io::ErrorKind::InvalidData, msg)
+}
+
+fn to_lit(x: i64) -> Lit {
+    Lit::new(Var::new(x.unsigned_abs() as usize - 1), x < 0)
+}
+
+fn parse_num<T: std::str::FromStr>(token: &str) -> io::Result<T> {
+    token.parse().map_err(|_| invalid("invalid number"))
+}
+
+/// Read a DRAT proof, in `ProofFormat::Drat` or `ProofFormat::BinaryDrat`.
+pub fn read_drat<R: Read>(reader: &mut R, format: ProofFormat) -> io::Result<Vec<DratClause>> {
+    let mut data = vec![];
+    reader.read_to_end(&mut data)?;
+    match format {
+        ProofFormat::Drat => parse_text_drat(&data),
+        ProofFormat::BinaryDrat => parse_binary_drat(&data),
+        ProofFormat::Lrat => Err(io::Error::new(
+            io::ErrorKind::InvalidInput,
+            "not a drat format",
+        )),
+    }
+}
+
+fn parse_text_drat(data: &[u8]) -> io::Result<Vec<DratClause>> {
+    let text = std::str::from_utf8(data).map_err(|_| invalid("invalid utf-8"))?;
+    let mut steps = vec![];
+    let mut lits = vec![];
+    let mut is_delete = false;
+    for line in text.lines() {
+        if line.starts_with('c') {
+            continue;
+        }
+        for token in line.split_whitespace() {
+            if token == "d" && lits.is_empty() {
+                is_delete = true;
+                continue;
+            }
+            match parse_num::<i64>(token)? {
+                0 => {
+                    let lits = std::mem::take(&mut lits);
+                    steps.push(if is_delete {
+                        DratClause::Delete(lits)
+                    } else {
+                        DratClause::Add(lits)
+                    });
+                    is_delete = false;
+                }
+                x => lits.push(to_lit(x)),
+            }
+        }
+    }
+    if !lits.is_empty() || is_delete {
+        return Err(invalid("unterminated clause"));
+    }
+    Ok(steps)
+}
+
+fn parse_binary_drat(data: &[u8]) -> io::Result<Vec<DratClause>> {
+    let mut steps = vec![];
+    let mut bytes = data.iter().copied();
+    while let Some(kind) = bytes.next() {
+        let is_delete = match kind {
+            b'a' => false,
+            b'd' => true,
+            _ => return Err(invalid("expected 'a' or 'd'")),
+        };
+        let mut lits = vec![];
+        loop {
+            let mut u: u64 = 0;
+            let mut shift = 0;
+            loop {
+                let byte = bytes.next().ok_or_else(|| invalid("unterminated clause"))?;
+                u |= ((byte & 127) as u64) << shift;
+                shift += 7;
+                if byte & 128 == 0 {
+                    break;
+                }
+            }
+            if u == 0 {
+                break;
+            }
+            if u < 2 {
+                return Err(invalid("invalid literal"));
+            }
+            lits.push(Lit::new(Var::new(u as usize / 2 - 1), u & 1 == 1));
+        }
+        steps.push(if is_delete {
+            DratClause::Delete(lits)
+        } else {
+            DratClause::Add(lits)
+        });
+    }
+    Ok(steps)
+}
+
+/// Read a proof in textual LRAT format.
+pub fn read_lrat<R: BufRead>(reader: &mut R) -> io::Result<Vec<LratStep>> {
+    let mut steps = vec![];
+    for line in reader.lines() {
+        let line = line?;
+        let tokens: Vec<&str> = line.split_whitespace().collect();
+        if tokens.is_empty() || tokens[0] == "c" {
+            continue;
+        }
+        let mut numbers = tokens[1..].iter();
+        if tokens.get(1) == Some(&"d") {
+            numbers.next();
+            let mut ids = vec![];
+            for &token in numbers.by_ref() {
+                match parse_num::<u64>(token)? {
+                    0 => break,
+                    id => ids.push(id),
+                }
+            }
+            steps.push(LratStep::Delete(ids));
+        } else {
+            let id = parse_num::<u64>(tokens[0])?;
+            let mut lits = vec![];
+            let mut hints = vec![];
+            for &token in numbers.by_ref() {
+                match parse_num::<i64>(token)? {
+                    0 => break,
+                    x => lits.push(to_lit(x)),
+                }
+            }
+            for &token in numbers.by_ref() {
+                match parse_num::<i64>(token)? {
+                    0 => break,
+                    x if x < 0 => return Err(invalid("rat hints are not supported")),
+                    x => hints.push(x as u64),
+                }
+            }
+            steps.push(LratStep::Add { id, lits, hints });
+        }
+    }
+    Ok(steps)
+}
@@ -0,0 +1,168 @@
+use super::state::State;
+use super::stats::SlsStats;
+use super::strategy::{random_unsat_clause, Strategy};
+use super::weighting::{increase_unsat_weights, EPSILON};
+use rand::RngCore;
+use solhop_types::Var;
+
+/// CCAnr: configuration checking with aspiration, on weighted scores.
+pub struct Ccanr {
+    smooth_threshold: f64,
+    rho: f64,
+    /// Whether a neighbour of each variable was flipped since its last flip
+    conf_changed: Vec<bool>,
+    /// Variables with a positive weighted score, and maybe some which no
+    /// longer have one, removed when selecting
+    good: Vec<usize>,
+    in_good: Vec<bool>,
+    /// Step of the last flip of each variable
+    last_flip: Vec<u64>,
+    step: u64,
+    total_weight: f64,
+    increases: u64,
+    smoothings: u64,
+}
+
+impl Ccanr {
+    pub fn new(n_vars: usize, smooth_threshold: f64, rho: f64) -> Self {
+        Ccanr {
+            smooth_threshold,
+            rho,
+            conf_changed: vec![true; n_vars],
+            good: vec![],
+            in_good: vec![false; n_vars],
+            last_flip: vec![0; n_vars],
+            step: 0,
+            total_weight: 0.0,
+            increases: 0,
+            smoothings: 0,
+        }
+    }
+
+    /// Add the variable to the good variables if its score became positive.
+    fn update_good(&mut self, state: &State, v: usize) {
+        if !self.in_good[v] && state.weighted_score(v) > EPSILON {
+            self.in_good[v] = true;
+            self.good.push(v);
+        }
+    }
+
+    /// Whether `v` has a higher score than `other`, or the same score and
+    /// was flipped less recently.
+    fn is_better(&self, state: &State, v: usize, other: Option<usize>) -> bool {
+        match other {
+            None => true,
+            Some(other) => {
+                let diff = state.weighted_score(v) - state.weighted_score(other);
+                diff > EPSILON || (diff > -EPSILON && self.last_flip[v] < self.last_flip[other])
+            }
+        }
+    }
+
+    /// Best good variable whose configuration changed, or if there is none,
+    /// best good variable whose score exceeds the mean clause weight.
+    fn pick_good(&mut self, state: &State) -> Option<usize> {
+        let mean_weight = self.total_weight / state.n_clauses() as f64;
+        let mut best_ccd = None;
+        let mut best_aspiration = None;
+        let mut i = 0;
+        while i < self.good.len() {
+            let v = self.good[i];
+            let score = state.weighted_score(v);
+            if score <= EPSILON {
+                self.in_good[v] = false;
+                self.good.swap_remove(i);
+                continue;
+            }
+            if self.conf_changed[v] {
+                if self.is_better(state, v, best_ccd) {
+                    best_ccd = Some(v);
+                }
+            } else if score > mean_weight && self.is_better(state, v, best_aspiration) {
+                best_aspiration = Some(v);
+            }
+            i += 1;
+        }
+        best_ccd.or(best_aspiration)
+    }
+
+    /// Increase the weights of the unsatisfied clauses, smoothing all weights
+    /// when their mean exceeds the threshold.
+    fn update_weights(&mut self, state: &mut State) {
+        increase_unsat_weights(state, 1.0);
+        self.total_weight += state.unsat().len() as f64;
+        self.increases += 1;
+        for i in 0..state.unsat().len() {
+            for j in 0..state.clause(state.unsat()[i]).len() {
+                let v = state.clause(state.unsat()[i])[j].var().index();
+                self.update_good(state, v);
+            }
+        }
+
+        let mean_weight = self.total_weight / state.n_clauses() as f64;
+        if mean_weight > self.smooth_threshold {
+            let rho = self.rho;
+            state.map_weights(|weight| rho * weight + (1.0 - rho) * mean_weight);
+            self.total_weight = state.weights().iter().sum();
+            self.smoothings += 1;
+            for v in 0..state.n_vars() {
+                self.update_good(state, v);
+            }
+        }
+    }
+}
+
+impl Strategy for Ccanr {
+    fn reset(&mut self, state: &mut State) {
+        state.init_weights(1.0);
+        self.total_weight = state.n_clauses() as f64;
+        self.good.clear();
+        for v in 0..state.n_vars() {
+            self.conf_changed[v] = true;
+            self.in_good[v] = false;
+            self.last_flip[v] = 0;
+            self.update_good(state, v);
+        }
+        self.step = 0;
+    }
+
+    fn pick_var(&mut self, state: &mut State, rng: &mut dyn RngCore) -> Option<usize> {
+        if let Some(v) = self.pick_good(state) {
+            return Some(v);
+        }
+
+        // Diversification: best variable of a random unsatisfied clause
+        self.update_weights(state);
+        let mut best = None;
+        for lit in state.clause(random_unsat_clause(state, rng)) {
+            let v = lit.var().index();
+            if self.is_better(state, v, best) {
+                best = Some(v);
+            }
+        }
+        best
+    }
+
+    fn after_flip(&mut self, state: &State, v: usize) {
+        self.step += 1;
+        self.last_flip[v] = self.step;
+        self.conf_changed[v] = false;
+        let var = Var::new(v);
+        for &lit in [var.pos_lit(), var.neg_lit()].iter() {
+            for &c in state.occurrences(lit) {
+                for other in state.clause(c) {
+                    let u = other.var().index();
+                    if u != v {
+                        self.conf_changed[u] = true;
+                    }
+                    self.update_good(state, u);
+                }
+            }
+        }
+    }
+
+    fn stats(&self, stats: &mut SlsStats) {
+        stats.weight_increases += self.increases;
+        stats.weight_smoothings += self.smoothings;
+    }
+}
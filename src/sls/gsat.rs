@@ -0,0 +1,48 @@
+use super::state::State;
+use super::strategy::{random_unsat_clause, with_prob, Strategy};
+use rand::{Rng, RngCore};
+
+/// GSAT with random walk.
+pub struct Gsat {
+    walk_prob: f64,
+    candidates: Vec<usize>,
+}
+
+impl Gsat {
+    pub fn new(walk_prob: f64) -> Self {
+        Gsat {
+            walk_prob,
+            candidates: vec![],
+        }
+    }
+}
+
+impl Strategy for Gsat {
+    fn pick_var(&mut self, state: &mut State, rng: &mut dyn RngCore) -> Option<usize> {
+        if with_prob(rng, self.walk_prob) {
+            let cl = state.clause(random_unsat_clause(state, rng));
+            if cl.is_empty() {
+                return None;
+            }
+            return Some(cl[rng.gen_range(0, cl.len())].var().index());
+        }
+
+        // Variables with the best make minus break
+        let mut best_score = i64::MIN;
+        self.candidates.clear();
+        for v in 0..state.n_vars() {
+            let score = state.make_count(v) as i64 - state.break_count(v) as i64;
+            if score > best_score {
+                best_score = score;
+                self.candidates.clear();
+            }
+            if score == best_score {
+                self.candidates.push(v);
+            }
+        }
+        if self.candidates.is_empty() {
+            return None;
+        }
+        Some(self.candidates[rng.gen_range(0, self.candidates.len())])
+    }
+}
@@ -1,13 +1,26 @@
-use rand::distributions::WeightedIndex;
+mod ccanr;
+mod gsat;
+mod novelty;
+mod options;
+mod probsat;
+mod state;
+mod stats;
+mod strategy;
+mod walksat;
+mod weighting;
+
+use crate::check::{check_model, InternalError};
+use crate::limits::Usage;
 use rand::prelude::*;
 use rayon::prelude::*;
 use solhop_types::{Clause, LBool, Lit, Solution};
+use state::State;
 use std::fs::File;
 use std::io;
+use std::sync::atomic::{AtomicBool, Ordering};
 
-/// Magic numbers used by local search.
-const C_MAKE: f32 = 0.5;
-const C_BREAK: f32 = 3.7;
+pub use options::{Algorithm, SlsOptions};
+pub use stats::SlsStats;
 
 /// Scoring function type.
 pub enum ScoreFnType {
@@ -21,10 +34,19 @@ pub enum ScoreFnType {
     Custom(Box<dyn Fn(i32, i32) -> f32>),
 }
 
+/// Result of a walk of the local search.
+struct Walk {
+    solution: Solution,
+    /// Number of unsatisfied clauses of the solution
+    n_unsat: usize,
+    stats: SlsStats,
+}
+
 /// SLS Solver.
 pub struct Solver {
     num_vars: usize,
     clauses: Vec<Clause>,
+    stats: SlsStats,
 }
 
 impl Solver {
@@ -50,6 +72,7 @@ impl Solver {
             Solver {
                 num_vars: n_vars,
                 clauses: clauses.into_iter().map(|lits| Clause { lits }).collect(),
+                stats: SlsStats::default(),
             }
         } else {
             panic!("Incorrect input format");
@@ -71,6 +94,16 @@ impl Solver {
         self.clauses.push(Clause { lits });
     }
 
+    /// Returns the statistics of the last local search.
+    pub fn stats(&self) -> &SlsStats {
+        &self.stats
+    }
+
+    /// Check that the model satisfies the clauses of the formula.
+    pub fn check_model(&self, model: &[bool]) -> Result<(), InternalError> {
+        check_model(&self.clauses, &[], model)
+    }
+
     /// Local Search based on probSAT. Tries for `max_tries` times
     /// with `max_flips` flips in each try.
     pub fn local_search(
@@ -80,150 +113,136 @@ impl Solver {
         score_fn_type: ScoreFnType,
         parallel: bool,
     ) -> Solution {
-        let mut curr_model = vec![false; self.num_vars as usize];
-        let mut best_model = vec![false; self.num_vars as usize];
+        self.solve(&SlsOptions {
+            max_tries,
+            max_flips,
+            algorithm: Algorithm::ProbSat(score_fn_type),
+            parallel,
+            ..SlsOptions::default()
+        })
+    }
+
+    /// Local search with the algorithm and limits given by the options.
+    /// Returns `Solution::Best` with the assignment with the fewest
+    /// unsatisfied clauses if no model is found.
+    pub fn solve(&mut self, options: &SlsOptions) -> Solution {
+        let walk = self.walk(options, &AtomicBool::new(false));
+        self.stats = walk.stats;
+        walk.solution
+    }
+
+    /// Portfolio local search: runs `n_walkers` walks in parallel, with the
+    /// options given by `walker` for each index, which should have different
+    /// seeds or algorithms. The options are made by the thread of the walk,
+    /// so custom scoring functions need not be `Send` or `Sync`. All walks
+    /// stop as soon as one of them finds a model. Otherwise returns the
+    /// `Solution::Best` with the fewest unsatisfied clauses across walks, or
+    /// `Solution::Unknown` if there are no walks.
+    ///
+    /// The counters of the statistics are summed over the walks, and the
+    /// clause weights are those of the walk giving the solution.
+    pub fn solve_portfolio<F>(&mut self, n_walkers: usize, walker: F) -> Solution
+    where
+        F: Fn(usize) -> SlsOptions + Sync,
+    {
+        let stop = AtomicBool::new(false);
+        let this = &*self;
+        let walks: Vec<Walk> = (0..n_walkers)
+            .into_par_iter()
+            .map(|i| this.walk(&walker(i), &stop))
+            .collect();
+
+        let mut stats = SlsStats::default();
+        for walk in walks.iter() {
+            stats.tries += walk.stats.tries;
+            stats.flips += walk.stats.flips;
+            stats.weight_increases += walk.stats.weight_increases;
+            stats.weight_smoothings += walk.stats.weight_smoothings;
+            stats.weight_transferred += walk.stats.weight_transferred;
+        }
+        let best = walks.into_iter().min_by_key(|walk| walk.n_unsat);
+        let solution = match best {
+            Some(walk) => {
+                stats.mean_weight = walk.stats.mean_weight;
+                stats.max_weight = walk.stats.max_weight;
+                walk.solution
+            }
+            None => Solution::Unknown,
+        };
+        self.stats = stats;
+        solution
+    }
+
+    /// Walk with the given options until a model is found, the tries, flips
+    /// or resource limits are reached, or `stop` is set. Sets `stop` when a model is found.
+    fn walk(&self, options: &SlsOptions, stop: &AtomicBool) -> Walk {
+        let mut curr_model = vec![false; self.num_vars];
+        let mut best_model = vec![false; self.num_vars];
         let mut best_n_unsat_clauses = self.clauses.len();
 
-        let mut clause_unsat = vec![1; self.clauses.len()];
+        let mut state = State::new(self.num_vars, &self.clauses);
+        let mut strategy = options.algorithm.strategy(self.num_vars);
 
-        let mut rng = thread_rng();
+        let mut rng = match options.seed {
+            Some(seed) => StdRng::seed_from_u64(seed),
+            None => StdRng::from_entropy(),
+        };
+        let mut stats = SlsStats::default();
+        let mut solution = None;
+        let mut usage = Usage::new();
 
-        for _ in 0..max_tries {
+        'tries: for _ in 0..options.max_tries {
+            if stop.load(Ordering::Relaxed) {
+                break;
+            }
+            stats.tries += 1;
             Solver::gen_rand_model(
                 &mut curr_model,
                 &mut rng,
                 &vec![LBool::Undef; self.num_vars],
             );
+            state.reset(&curr_model, options.parallel);
+            strategy.reset(&mut state);
 
-            for _ in 0..max_flips {
-                let n_unsat_clauses = if parallel {
-                    self.clauses
-                        .par_iter()
-                        .zip(clause_unsat.par_iter_mut())
-                        .map(|(cl, cl_us)| {
-                            let mut clause_unsat = 1;
-                            for lit in &cl.lits {
-                                let var = lit.var();
-                                if lit.sign() != curr_model[var.index()] {
-                                    clause_unsat = 0;
-                                    break;
-                                }
-                            }
-                            *cl_us = clause_unsat;
-                            clause_unsat
-                        })
-                        .sum()
-                } else {
-                    self.clauses
-                        .iter()
-                        .zip(clause_unsat.iter_mut())
-                        .map(|(cl, cl_us)| {
-                            let mut clause_unsat = 1;
-                            for lit in &cl.lits {
-                                let var = lit.var();
-                                if lit.sign() != curr_model[var.index()] {
-                                    clause_unsat = 0;
-                                    break;
-                                }
-                            }
-                            *cl_us = clause_unsat;
-                            clause_unsat
-                        })
-                        .sum()
-                };
-
+            for _ in 0..options.max_flips {
+                let n_unsat_clauses = state.unsat().len();
                 if n_unsat_clauses == 0 {
-                    return Solution::Sat(curr_model.iter().copied().collect());
+                    stop.store(true, Ordering::Relaxed);
+                    solution = Some(Solution::Sat(state.model().to_vec()));
+                    best_n_unsat_clauses = 0;
+                    break 'tries;
                 } else if n_unsat_clauses < best_n_unsat_clauses {
-                    best_model.clone_from_slice(&curr_model);
+                    best_model.copy_from_slice(state.model());
                     best_n_unsat_clauses = n_unsat_clauses;
                 }
-
-                let dist = WeightedIndex::new(&clause_unsat).unwrap();
-                let selected_clause = dist.sample(&mut rng);
-
-                let Clause { lits: cl } = &self.clauses[selected_clause];
-                let mut scores = vec![0.0; self.num_vars as usize];
-                for x in cl {
-                    let var_i = x.var();
-
-                    curr_model[var_i.index()] = !curr_model[var_i.index()];
-                    let (break_count, make_count) = if parallel {
-                        self.clauses
-                            .par_iter()
-                            .zip(clause_unsat.par_iter())
-                            .map(|(Clause { lits: cl }, cl_us)| {
-                                let mut cl_unsat = 1;
-                                for &lit in cl {
-                                    let var = lit.var();
-                                    if lit.sign() != curr_model[var.index()] {
-                                        cl_unsat = 0;
-                                        break;
-                                    }
-                                }
-
-                                if cl_unsat != *cl_us {
-                                    if cl_unsat == 1 {
-                                        // break_count += 1;
-                                        (1, 0)
-                                    } else {
-                                        // make_count += 1;
-                                        (0, 1)
-                                    }
-                                } else {
-                                    (0, 0)
-                                }
-                            })
-                            .reduce(|| (0, 0), |a, b| (a.0 + b.0, a.1 + b.1))
-                    } else {
-                        self.clauses
-                            .iter()
-                            .zip(clause_unsat.iter())
-                            .map(|(Clause { lits: cl }, cl_us)| {
-                                let mut cl_unsat = 1;
-                                for &lit in cl {
-                                    let var = lit.var();
-                                    if lit.sign() != curr_model[var.index()] {
-                                        cl_unsat = 0;
-                                        break;
-                                    }
-                                }
-
-                                if cl_unsat != *cl_us {
-                                    if cl_unsat == 1 {
-                                        // break_count += 1;
-                                        (1, 0)
-                                    } else {
-                                        // make_count += 1;
-                                        (0, 1)
-                                    }
-                                } else {
-                                    (0, 0)
-                                }
-                            })
-                            .fold((0, 0), |a, b| (a.0 + b.0, a.1 + b.1))
-                    };
-
-                    curr_model[var_i.index()] = !curr_model[var_i.index()];
-
-                    scores[var_i.index()] = match &score_fn_type {
-                        ScoreFnType::Rand => 1.0,
-                        ScoreFnType::Poly => 1.0 / (1.0 + break_count as f32).powf(C_BREAK),
-                        ScoreFnType::Exp => C_MAKE.powi(make_count) / C_BREAK.powi(break_count),
-                        ScoreFnType::Custom(f) => f(make_count, break_count),
-                    };
+                if stop.load(Ordering::Relaxed) || options.limits.reached(&mut usage) {
+                    break 'tries;
                 }
 
-                let dist_var = WeightedIndex::new(&scores).unwrap();
-                let selected_var = dist_var.sample(&mut rng);
-                curr_model[selected_var] = !curr_model[selected_var];
+                if let Some(v) = strategy.pick_var(&mut state, &mut rng) {
+                    state.flip(v);
+                    strategy.after_flip(&state, v);
+                    stats.flips += 1;
+                    usage.flips += 1;
+                }
             }
         }
 
-        Solution::Best(best_model.iter().copied().collect())
+        strategy.stats(&mut stats);
+        let weights = state.weights();
+        if !weights.is_empty() {
+            stats.mean_weight = weights.iter().sum::<f64>() / weights.len() as f64;
+            stats.max_weight = weights.iter().copied().fold(0.0, f64::max);
+        }
+
+        Walk {
+            solution: solution.unwrap_or(Solution::Best(best_model)),
+            n_unsat: best_n_unsat_clauses,
+            stats,
+        }
     }
 
-    fn gen_rand_model<T>(model: &mut Vec<bool>, rng: &mut T, l_model: &[LBool])
+    fn gen_rand_model<T>(model: &mut [bool], rng: &mut T, l_model: &[LBool])
     where
         T: rand::Rng,
     {
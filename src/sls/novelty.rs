@@ -0,0 +1,109 @@
+use super::state::State;
+use super::strategy::{random_unsat_clause, with_prob, Strategy};
+use rand::{Rng, RngCore};
+
+/// Noise adaptation parameters of adaptive Novelty+, from Hoos (2002).
+const PHI: f64 = 0.2;
+const THETA: f64 = 1.0 / 6.0;
+
+/// Novelty+ and adaptive Novelty+.
+pub struct Novelty {
+    noise: f64,
+    walk_prob: f64,
+    adaptive: bool,
+    /// Step of the last flip of each variable
+    last_flip: Vec<u64>,
+    step: u64,
+    /// Number of unsatisfied clauses at the last noise adaptation
+    adapt_unsat: usize,
+    adapt_step: u64,
+}
+
+impl Novelty {
+    pub fn new(n_vars: usize, noise: f64, walk_prob: f64, adaptive: bool) -> Self {
+        Novelty {
+            noise,
+            walk_prob,
+            adaptive,
+            last_flip: vec![0; n_vars],
+            step: 0,
+            adapt_unsat: 0,
+            adapt_step: 0,
+        }
+    }
+
+    fn adapt_noise(&mut self, state: &State) {
+        let n_unsat = state.unsat().len();
+        if n_unsat < self.adapt_unsat {
+            self.noise -= self.noise * PHI / 2.0;
+        } else if (self.step - self.adapt_step) as f64 > THETA * state.n_clauses() as f64 {
+            self.noise += (1.0 - self.noise) * PHI;
+        } else {
+            return;
+        }
+        self.adapt_unsat = n_unsat;
+        self.adapt_step = self.step;
+    }
+}
+
+impl Strategy for Novelty {
+    fn reset(&mut self, state: &mut State) {
+        for step in self.last_flip.iter_mut() {
+            *step = 0;
+        }
+        self.step = 0;
+        if self.adaptive {
+            self.noise = 0.0;
+            self.adapt_unsat = state.unsat().len();
+            self.adapt_step = 0;
+        }
+    }
+
+    fn pick_var(&mut self, state: &mut State, rng: &mut dyn RngCore) -> Option<usize> {
+        let cl = state.clause(random_unsat_clause(state, rng));
+        if cl.is_empty() {
+            return None;
+        }
+        if with_prob(rng, self.walk_prob) {
+            return Some(cl[rng.gen_range(0, cl.len())].var().index());
+        }
+
+        // Best and second best variables by make minus break,
+        // ties broken in favor of the least recently flipped
+        let score = |v: usize| state.make_count(v) as i64 - state.break_count(v) as i64;
+        let better = |v: usize, u: usize| {
+            score(v) > score(u) || (score(v) == score(u) && self.last_flip[v] < self.last_flip[u])
+        };
+        let mut best = cl[0].var().index();
+        let mut second = None;
+        let mut youngest = best;
+        for lit in cl[1..].iter() {
+            let v = lit.var().index();
+            if better(v, best) {
+                second = Some(best);
+                best = v;
+            } else {
+                match second {
+                    Some(u) if !better(v, u) => {}
+                    _ => second = Some(v),
+                }
+            }
+            if self.last_flip[v] > self.last_flip[youngest] {
+                youngest = v;
+            }
+        }
+
+        match second {
+            Some(second) if best == youngest && with_prob(rng, self.noise) => Some(second),
+            _ => Some(best),
+        }
+    }
+
+    fn after_flip(&mut self, state: &State, v: usize) {
+        self.step += 1;
+        self.last_flip[v] = self.step;
+        if self.adaptive {
+            self.adapt_noise(state);
+        }
+    }
+}
@@ -0,0 +1,114 @@
+use super::ScoreFnType;
+use crate::limits::Limits;
+
+/// Local search algorithm and its parameters.
+pub enum Algorithm {
+    /// probSAT with the given scoring function.
+    ProbSat(ScoreFnType),
+    /// WalkSAT with the SKC variable selection. If no variable of the clause
+    /// can be flipped without breaking other clauses, a random one is flipped
+    /// with probability `noise`, otherwise one with the fewest breaks.
+    WalkSat {
+        /// Noise probability
+        noise: f64,
+    },
+    /// Novelty+. The best variable of the clause by make minus break is
+    /// flipped, unless it is the most recently flipped one, in which case the
+    /// second best is flipped with probability `noise`.
+    NoveltyPlus {
+        /// Noise probability
+        noise: f64,
+        /// Probability to flip a random variable of the clause instead
+        walk_prob: f64,
+    },
+    /// Novelty+ with a noise adapted during search: it is increased when the
+    /// number of unsatisfied clauses stagnates, and decreased when it improves.
+    AdaptiveNoveltyPlus {
+        /// Probability to flip a random variable of the clause instead
+        walk_prob: f64,
+    },
+    /// GSAT with random walk. The variable with the best make minus break of
+    /// the formula is flipped.
+    Gsat {
+        /// Probability to flip a random variable of an unsatisfied clause instead
+        walk_prob: f64,
+    },
+    /// Scaling and probabilistic smoothing (SAPS). The variable of the
+    /// unsatisfied clauses with the best weighted make minus break is flipped
+    /// if it decreases the total weight of the unsatisfied clauses. Otherwise
+    /// the weights of the unsatisfied clauses are multiplied by `alpha`, and
+    /// with probability `smooth_prob` all weights are moved towards their mean.
+    Saps {
+        /// Scaling factor of the weights of the unsatisfied clauses
+        alpha: f64,
+        /// Part of each weight kept by smoothing, the rest being replaced by the mean
+        rho: f64,
+        /// Probability to smooth the weights after scaling
+        smooth_prob: f64,
+        /// Probability to flip a random variable at a local minimum instead
+        walk_prob: f64,
+    },
+    /// Pure additive weighting scheme (PAWS). As SAPS, but the weights of the
+    /// unsatisfied clauses are increased by one at local minima, and every
+    /// `max_increases` increases the weights above one are decreased by one.
+    Paws {
+        /// Probability to make a move which keeps the total weight unchanged
+        flat_move_prob: f64,
+        /// Number of weight increases between two weight decreases
+        max_increases: u32,
+    },
+    /// Divide and distribute fixed weights (DDFW). The clauses start with
+    /// `init_weight` and, at local minima, each unsatisfied clause takes weight
+    /// from its heaviest satisfied neighbour, so the total weight is constant.
+    Ddfw {
+        /// Initial weight of every clause
+        init_weight: f64,
+        /// Probability to make a move which keeps the total weight unchanged
+        flat_move_prob: f64,
+    },
+    /// CCAnr. The variable with the best weighted make minus break among the
+    /// configuration changed decreasing (CCD) variables is flipped, i.e.
+    /// among those with a positive score a neighbour of which was flipped
+    /// since their last flip. Without CCD variables, the best variable whose
+    /// score exceeds the mean clause weight is flipped (aspiration). Otherwise
+    /// the weights of the unsatisfied clauses are increased by one, and the
+    /// best variable of a random unsatisfied clause is flipped. Ties are
+    /// broken in favour of the least recently flipped variable.
+    Ccanr {
+        /// Mean clause weight above which weights are smoothed
+        smooth_threshold: f64,
+        /// Part of each weight kept by smoothing, the rest being replaced by the mean
+        rho: f64,
+    },
+}
+
+/// Local search options.
+pub struct SlsOptions {
+    /// Number of tries, each from a random assignment
+    pub max_tries: u32,
+    /// Number of flips in each try
+    pub max_flips: u32,
+    /// Local search algorithm
+    pub algorithm: Algorithm,
+    /// Compute the clause counts of each try in parallel
+    pub parallel: bool,
+    /// Seed of the random number generator, which makes the search
+    /// reproducible whatever the number of threads. If `None`, the seed is
+    /// taken from the operating system.
+    pub seed: Option<u64>,
+    /// Resource limits of the search, in addition to the tries and flips
+    pub limits: Limits,
+}
+
+impl Default for SlsOptions {
+    fn default() -> Self {
+        SlsOptions {
+            max_tries: 100,
+            max_flips: 100_000,
+            algorithm: Algorithm::ProbSat(ScoreFnType::Poly),
+            parallel: false,
+            seed: None,
+            limits: Limits::default(),
+        }
+    }
+}
@@ -0,0 +1,53 @@
+use super::state::State;
+use super::strategy::{random_unsat_clause, Strategy};
+use super::ScoreFnType;
+use rand::distributions::{Distribution, WeightedIndex};
+use rand::{Rng, RngCore};
+
+/// Magic numbers used by local search.
+const C_MAKE: f32 = 0.5;
+const C_BREAK: f32 = 3.7;
+
+/// probSAT: flips a variable of a random unsatisfied clause, with
+/// probabilities given by the scoring function of its make and break counts.
+pub struct ProbSat<'a> {
+    score_fn_type: &'a ScoreFnType,
+    scores: Vec<f32>,
+}
+
+impl<'a> ProbSat<'a> {
+    pub fn new(score_fn_type: &'a ScoreFnType) -> Self {
+        ProbSat {
+            score_fn_type,
+            scores: vec![],
+        }
+    }
+}
+
+impl Strategy for ProbSat<'_> {
+    fn pick_var(&mut self, state: &mut State, rng: &mut dyn RngCore) -> Option<usize> {
+        let cl = state.clause(random_unsat_clause(state, rng));
+        if cl.is_empty() {
+            return None;
+        }
+
+        self.scores.clear();
+        for x in cl {
+            let var_i = x.var().index();
+            let make_count = state.make_count(var_i) as i32;
+            let break_count = state.break_count(var_i) as i32;
+            self.scores.push(match self.score_fn_type {
+                ScoreFnType::Rand => 1.0,
+                ScoreFnType::Poly => 1.0 / (1.0 + break_count as f32).powf(C_BREAK),
+                ScoreFnType::Exp => C_MAKE.powi(make_count) / C_BREAK.powi(break_count),
+                ScoreFnType::Custom(f) => f(make_count, break_count),
+            });
+        }
+
+        let selected = match WeightedIndex::new(&self.scores) {
+            Ok(dist_var) => dist_var.sample(rng),
+            Err(_) => rng.gen_range(0, cl.len()),
+        };
+        Some(cl[selected].var().index())
+    }
+}
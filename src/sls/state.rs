@@ -0,0 +1,293 @@
+use rayon::prelude::*;
+use solhop_types::{Clause, Lit, Var};
+
+/// Assignment of a local search walk, with the counts needed to flip a
+/// variable in time proportional to its number of occurrences.
+pub struct State {
+    /// Clauses without duplicate literals, tautologies removed
+    clauses: Vec<Vec<Lit>>,
+    /// Clauses containing each literal
+    occurrences: Vec<Vec<usize>>,
+    model: Vec<bool>,
+    /// Number of true literals of each clause
+    n_true: Vec<u32>,
+    /// Xor of the variables of the true literals of each clause,
+    /// i.e. the critical variable of the clauses with one true literal
+    true_vars: Vec<usize>,
+    /// Number of clauses that become unsatisfied when flipping each variable
+    break_count: Vec<u32>,
+    /// Number of clauses that become satisfied when flipping each variable
+    make_count: Vec<u32>,
+    unsat: Vec<usize>,
+    /// Position of each unsatisfied clause in `unsat`
+    unsat_pos: Vec<usize>,
+    /// Clause weights, empty unless a clause weighting algorithm is used
+    weights: Vec<f64>,
+    /// Total weight of the clauses that become satisfied when flipping each variable
+    weighted_make: Vec<f64>,
+    /// Total weight of the clauses that become unsatisfied when flipping each variable
+    weighted_break: Vec<f64>,
+}
+
+impl State {
+    pub fn new(n_vars: usize, clauses: &[Clause]) -> Self {
+        let mut occurrences = vec![vec![]; 2 * n_vars];
+        let mut simplified = vec![];
+        'clauses: for clause in clauses {
+            let mut lits = clause.lits.clone();
+            lits.sort_by_key(|lit| lit.index());
+            lits.dedup();
+            for i in 1..lits.len() {
+                if lits[i - 1] == !lits[i] {
+                    continue 'clauses;
+                }
+            }
+            for lit in lits.iter() {
+                occurrences[lit.index()].push(simplified.len());
+            }
+            simplified.push(lits);
+        }
+        let n_clauses = simplified.len();
+        State {
+            clauses: simplified,
+            occurrences,
+            model: vec![false; n_vars],
+            n_true: vec![0; n_clauses],
+            true_vars: vec![0; n_clauses],
+            break_count: vec![0; n_vars],
+            make_count: vec![0; n_vars],
+            unsat: vec![],
+            unsat_pos: vec![0; n_clauses],
+            weights: vec![],
+            weighted_make: vec![],
+            weighted_break: vec![],
+        }
+    }
+
+    /// Start a walk from the given model, computing the counts of all
+    /// clauses, in parallel if `parallel` is set.
+    pub fn reset(&mut self, model: &[bool], parallel: bool) {
+        self.model.copy_from_slice(model);
+        let model = &self.model;
+        let count = |lits: &Vec<Lit>| {
+            lits.iter()
+                .filter(|lit| model[lit.var().index()] != lit.sign())
+                .fold((0, 0), |(n, vars), lit| (n + 1, vars ^ lit.var().index()))
+        };
+        let counts: Vec<(u32, usize)> = if parallel {
+            self.clauses.par_iter().map(count).collect()
+        } else {
+            self.clauses.iter().map(count).collect()
+        };
+
+        self.unsat.clear();
+        for v in 0..self.model.len() {
+            self.break_count[v] = 0;
+            self.make_count[v] = 0;
+        }
+        for (c, (n, vars)) in counts.into_iter().enumerate() {
+            self.n_true[c] = n;
+            self.true_vars[c] = vars;
+            match n {
+                0 => {
+                    self.add_unsat(c);
+                    self.update_make_count(c, true);
+                }
+                1 => self.break_count[vars] += 1,
+                _ => {}
+            }
+        }
+        if !self.weights.is_empty() {
+            self.update_weighted_counts();
+        }
+    }
+
+    pub fn n_vars(&self) -> usize {
+        self.model.len()
+    }
+
+    pub fn n_clauses(&self) -> usize {
+        self.clauses.len()
+    }
+
+    pub fn model(&self) -> &[bool] {
+        &self.model
+    }
+
+    pub fn clause(&self, c: usize) -> &[Lit] {
+        &self.clauses[c]
+    }
+
+    /// Unsatisfied clauses, in no particular order.
+    pub fn unsat(&self) -> &[usize] {
+        &self.unsat
+    }
+
+    /// Number of clauses that become unsatisfied by flipping `v`.
+    pub fn break_count(&self, v: usize) -> u32 {
+        self.break_count[v]
+    }
+
+    /// Number of clauses that become satisfied by flipping `v`.
+    pub fn make_count(&self, v: usize) -> u32 {
+        self.make_count[v]
+    }
+
+    /// Set the weight of every clause, enabling the weighted counts.
+    pub fn init_weights(&mut self, weight: f64) {
+        self.weights = vec![weight; self.clauses.len()];
+        self.update_weighted_counts();
+    }
+
+    pub fn weights(&self) -> &[f64] {
+        &self.weights
+    }
+
+    /// Set the weight of the clause, updating the weighted counts of its variables.
+    pub fn set_weight(&mut self, c: usize, weight: f64) {
+        let delta = weight - self.weights[c];
+        self.weights[c] = weight;
+        match self.n_true[c] {
+            0 => {
+                for lit in self.clauses[c].iter() {
+                    self.weighted_make[lit.var().index()] += delta;
+                }
+            }
+            1 => self.weighted_break[self.true_vars[c]] += delta,
+            _ => {}
+        }
+    }
+
+    /// Replace the weight of every clause by `f(weight)`, then recompute the
+    /// weighted counts, which also discards the rounding errors of the
+    /// incremental updates.
+    pub fn map_weights<F: Fn(f64) -> f64>(&mut self, f: F) {
+        for weight in self.weights.iter_mut() {
+            *weight = f(*weight);
+        }
+        self.update_weighted_counts();
+    }
+
+    /// Decrease in the total weight of the unsatisfied clauses by flipping `v`,
+    /// i.e. its weighted make minus its weighted break.
+    pub fn weighted_score(&self, v: usize) -> f64 {
+        self.weighted_make[v] - self.weighted_break[v]
+    }
+
+    pub fn is_sat(&self, c: usize) -> bool {
+        self.n_true[c] > 0
+    }
+
+    /// Clauses containing the literal.
+    pub fn occurrences(&self, lit: Lit) -> &[usize] {
+        &self.occurrences[lit.index()]
+    }
+
+    fn update_weighted_counts(&mut self) {
+        self.weighted_make = vec![0.0; self.model.len()];
+        self.weighted_break = vec![0.0; self.model.len()];
+        for c in 0..self.clauses.len() {
+            match self.n_true[c] {
+                0 => {
+                    for lit in self.clauses[c].iter() {
+                        self.weighted_make[lit.var().index()] += self.weights[c];
+                    }
+                }
+                1 => self.weighted_break[self.true_vars[c]] += self.weights[c],
+                _ => {}
+            }
+        }
+    }
+
+    /// Count the clause, which became unsatisfied or satisfied,
+    /// in the make counts of its variables.
+    fn update_make_count(&mut self, c: usize, unsat: bool) {
+        let weight = self.weights.get(c).copied();
+        for lit in self.clauses[c].iter() {
+            let v = lit.var().index();
+            if unsat {
+                self.make_count[v] += 1;
+            } else {
+                self.make_count[v] -= 1;
+            }
+            if let Some(weight) = weight {
+                if unsat {
+                    self.weighted_make[v] += weight;
+                } else {
+                    self.weighted_make[v] -= weight;
+                }
+            }
+        }
+    }
+
+    /// Count the clause, of which `v` became or stopped being the critical
+    /// variable, in the break counts of `v`.
+    fn update_break_count(&mut self, v: usize, c: usize, critical: bool) {
+        if critical {
+            self.break_count[v] += 1;
+        } else {
+            self.break_count[v] -= 1;
+        }
+        if let Some(&weight) = self.weights.get(c) {
+            if critical {
+                self.weighted_break[v] += weight;
+            } else {
+                self.weighted_break[v] -= weight;
+            }
+        }
+    }
+
+    /// Literal of `v` that becomes true by flipping `v`.
+    fn flipped_lit(&self, v: usize) -> Lit {
+        Lit::new(Var::new(v), self.model[v])
+    }
+
+    fn add_unsat(&mut self, c: usize) {
+        self.unsat_pos[c] = self.unsat.len();
+        self.unsat.push(c);
+    }
+
+    fn remove_unsat(&mut self, c: usize) {
+        let pos = self.unsat_pos[c];
+        let last = *self.unsat.last().unwrap();
+        self.unsat[pos] = last;
+        self.unsat_pos[last] = pos;
+        self.unsat.pop();
+    }
+
+    pub fn flip(&mut self, v: usize) {
+        let made = self.flipped_lit(v);
+        self.model[v] = !self.model[v];
+
+        for i in 0..self.occurrences[made.index()].len() {
+            let c = self.occurrences[made.index()][i];
+            self.n_true[c] += 1;
+            self.true_vars[c] ^= v;
+            match self.n_true[c] {
+                1 => {
+                    self.remove_unsat(c);
+                    self.update_make_count(c, false);
+                    self.update_break_count(v, c, true);
+                }
+                2 => self.update_break_count(self.true_vars[c] ^ v, c, false),
+                _ => {}
+            }
+        }
+
+        let broken = !made;
+        for i in 0..self.occurrences[broken.index()].len() {
+            let c = self.occurrences[broken.index()][i];
+            self.n_true[c] -= 1;
+            self.true_vars[c] ^= v;
+            match self.n_true[c] {
+                0 => {
+                    self.add_unsat(c);
+                    self.update_make_count(c, true);
+                    self.update_break_count(v, c, false);
+                }
+                1 => self.update_break_count(self.true_vars[c], c, true),
+                _ => {}
+            }
+        }
+    }
+}
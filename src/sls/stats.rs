@@ -0,0 +1,19 @@
+/// Statistics of the last local search.
+#[derive(Clone, Debug, Default)]
+pub struct SlsStats {
+    /// Number of tries
+    pub tries: u64,
+    /// Number of flips
+    pub flips: u64,
+    /// Number of local minima at which the weights of the unsatisfied clauses
+    /// were increased, by clause weighting algorithms
+    pub weight_increases: u64,
+    /// Number of weight smoothings, or weight decreases for PAWS
+    pub weight_smoothings: u64,
+    /// Total weight moved from satisfied to unsatisfied clauses by DDFW
+    pub weight_transferred: f64,
+    /// Mean clause weight at the end of the search
+    pub mean_weight: f64,
+    /// Maximum clause weight at the end of the search
+    pub max_weight: f64,
+}
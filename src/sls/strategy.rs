@@ -0,0 +1,72 @@
+use super::ccanr::Ccanr;
+use super::gsat::Gsat;
+use super::novelty::Novelty;
+use super::options::Algorithm;
+use super::probsat::ProbSat;
+use super::state::State;
+use super::stats::SlsStats;
+use super::walksat::WalkSat;
+use super::weighting::{Ddfw, Paws, Saps};
+use rand::{Rng, RngCore};
+
+/// Variable selection of a local search algorithm.
+pub trait Strategy {
+    /// Start a new try from the assignment of the state.
+    fn reset(&mut self, _state: &mut State) {}
+
+    /// Select the variable to flip, some clause being unsatisfied.
+    /// Returns `None` if no flip can satisfy the selected clause, or if the
+    /// step updates the clause weights instead.
+    fn pick_var(&mut self, state: &mut State, rng: &mut dyn RngCore) -> Option<usize>;
+
+    /// Called after flipping the variable.
+    fn after_flip(&mut self, _state: &State, _v: usize) {}
+
+    /// Add the counters of the strategy to the statistics.
+    fn stats(&self, _stats: &mut SlsStats) {}
+}
+
+impl Algorithm {
+    pub(crate) fn strategy(&self, n_vars: usize) -> Box<dyn Strategy + '_> {
+        match self {
+            Algorithm::ProbSat(score_fn_type) => Box::new(ProbSat::new(score_fn_type)),
+            Algorithm::WalkSat { noise } => Box::new(WalkSat::new(*noise)),
+            Algorithm::NoveltyPlus { noise, walk_prob } => {
+                Box::new(Novelty::new(n_vars, *noise, *walk_prob, false))
+            }
+            Algorithm::AdaptiveNoveltyPlus { walk_prob } => {
+                Box::new(Novelty::new(n_vars, 0.0, *walk_prob, true))
+            }
+            Algorithm::Gsat { walk_prob } => Box::new(Gsat::new(*walk_prob)),
+            Algorithm::Saps {
+                alpha,
+                rho,
+                smooth_prob,
+                walk_prob,
+            } => Box::new(Saps::new(n_vars, *alpha, *rho, *smooth_prob, *walk_prob)),
+            Algorithm::Paws {
+                flat_move_prob,
+                max_increases,
+            } => Box::new(Paws::new(n_vars, *flat_move_prob, *max_increases)),
+            Algorithm::Ddfw {
+                init_weight,
+                flat_move_prob,
+            } => Box::new(Ddfw::new(n_vars, *init_weight, *flat_move_prob)),
+            Algorithm::Ccanr {
+                smooth_threshold,
+                rho,
+            } => Box::new(Ccanr::new(n_vars, *smooth_threshold, *rho)),
+        }
+    }
+}
+
+/// Uniformly selected unsatisfied clause.
+pub fn random_unsat_clause(state: &State, rng: &mut dyn RngCore) -> usize {
+    let unsat = state.unsat();
+    unsat[rng.gen_range(0, unsat.len())]
+}
+
+/// Returns true with probability `p`.
+pub fn with_prob(rng: &mut dyn RngCore, p: f64) -> bool {
+    rng.gen::<f64>() < p
+}
@@ -0,0 +1,48 @@
+use super::state::State;
+use super::strategy::{random_unsat_clause, with_prob, Strategy};
+use rand::{Rng, RngCore};
+
+/// WalkSAT with the SKC variable selection.
+pub struct WalkSat {
+    noise: f64,
+    candidates: Vec<usize>,
+}
+
+impl WalkSat {
+    pub fn new(noise: f64) -> Self {
+        WalkSat {
+            noise,
+            candidates: vec![],
+        }
+    }
+}
+
+impl Strategy for WalkSat {
+    fn pick_var(&mut self, state: &mut State, rng: &mut dyn RngCore) -> Option<usize> {
+        let cl = state.clause(random_unsat_clause(state, rng));
+        if cl.is_empty() {
+            return None;
+        }
+
+        // Variables with the fewest breaks
+        let mut min_break = u32::MAX;
+        self.candidates.clear();
+        for lit in cl {
+            let v = lit.var().index();
+            let break_count = state.break_count(v);
+            if break_count < min_break {
+                min_break = break_count;
+                self.candidates.clear();
+            }
+            if break_count == min_break {
+                self.candidates.push(v);
+            }
+        }
+
+        if min_break > 0 && with_prob(rng, self.noise) {
+            Some(cl[rng.gen_range(0, cl.len())].var().index())
+        } else {
+            Some(self.candidates[rng.gen_range(0, self.candidates.len())])
+        }
+    }
+}
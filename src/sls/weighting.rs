@@ -0,0 +1,290 @@
+use super::state::State;
+use super::stats::SlsStats;
+use super::strategy::{random_unsat_clause, with_prob, Strategy};
+use rand::{Rng, RngCore};
+
+/// Tolerance of the comparisons of weighted scores, which are sums of weights.
+pub const EPSILON: f64 = 1e-9;
+
+/// Number of random clauses sampled by DDFW to find a donor of weight when the
+/// unsatisfied clause has no heavy satisfied neighbour.
+const DONOR_SAMPLES: usize = 16;
+
+/// Variables of the unsatisfied clauses with the best weighted score.
+struct Candidates {
+    vars: Vec<usize>,
+    /// Stamp of the last selection in which each variable was scored
+    seen: Vec<u64>,
+    stamp: u64,
+}
+
+impl Candidates {
+    fn new(n_vars: usize) -> Self {
+        Candidates {
+            vars: vec![],
+            seen: vec![0; n_vars],
+            stamp: 0,
+        }
+    }
+
+    /// Random variable with the best weighted score, with its score.
+    fn best(&mut self, state: &State, rng: &mut dyn RngCore) -> Option<(usize, f64)> {
+        self.stamp += 1;
+        self.vars.clear();
+        let mut best_score = f64::NEG_INFINITY;
+        for &c in state.unsat() {
+            for lit in state.clause(c) {
+                let v = lit.var().index();
+                if self.seen[v] == self.stamp {
+                    continue;
+                }
+                self.seen[v] = self.stamp;
+                let score = state.weighted_score(v);
+                if score > best_score + EPSILON {
+                    best_score = score;
+                    self.vars.clear();
+                }
+                if score >= best_score - EPSILON {
+                    self.vars.push(v);
+                }
+            }
+        }
+        if self.vars.is_empty() {
+            None
+        } else {
+            Some((self.vars[rng.gen_range(0, self.vars.len())], best_score))
+        }
+    }
+}
+
+/// Improving move, or flat move with probability `flat_move_prob`.
+fn weighted_move(
+    candidates: &mut Candidates,
+    state: &State,
+    rng: &mut dyn RngCore,
+    flat_move_prob: f64,
+) -> Option<usize> {
+    match candidates.best(state, rng) {
+        Some((v, score)) if score > EPSILON => Some(v),
+        Some((v, score)) if score > -EPSILON && with_prob(rng, flat_move_prob) => Some(v),
+        _ => None,
+    }
+}
+
+/// Random variable of an unsatisfied clause.
+fn random_walk(state: &State, rng: &mut dyn RngCore) -> Option<usize> {
+    let cl = state.clause(random_unsat_clause(state, rng));
+    if cl.is_empty() {
+        None
+    } else {
+        Some(cl[rng.gen_range(0, cl.len())].var().index())
+    }
+}
+
+/// Add `delta` to the weight of every unsatisfied clause.
+pub fn increase_unsat_weights(state: &mut State, delta: f64) {
+    for i in 0..state.unsat().len() {
+        let c = state.unsat()[i];
+        state.set_weight(c, state.weights()[c] + delta);
+    }
+}
+
+/// Scaling and probabilistic smoothing.
+pub struct Saps {
+    alpha: f64,
+    rho: f64,
+    smooth_prob: f64,
+    walk_prob: f64,
+    candidates: Candidates,
+    increases: u64,
+    smoothings: u64,
+}
+
+impl Saps {
+    pub fn new(n_vars: usize, alpha: f64, rho: f64, smooth_prob: f64, walk_prob: f64) -> Self {
+        Saps {
+            alpha,
+            rho,
+            smooth_prob,
+            walk_prob,
+            candidates: Candidates::new(n_vars),
+            increases: 0,
+            smoothings: 0,
+        }
+    }
+}
+
+impl Strategy for Saps {
+    fn reset(&mut self, state: &mut State) {
+        state.init_weights(1.0);
+    }
+
+    fn pick_var(&mut self, state: &mut State, rng: &mut dyn RngCore) -> Option<usize> {
+        if let Some(v) = weighted_move(&mut self.candidates, state, rng, 0.0) {
+            return Some(v);
+        }
+        if with_prob(rng, self.walk_prob) {
+            return random_walk(state, rng);
+        }
+
+        for i in 0..state.unsat().len() {
+            let c = state.unsat()[i];
+            state.set_weight(c, state.weights()[c] * self.alpha);
+        }
+        self.increases += 1;
+        if with_prob(rng, self.smooth_prob) {
+            let mean = state.weights().iter().sum::<f64>() / state.n_clauses() as f64;
+            let rho = self.rho;
+            state.map_weights(|weight| rho * weight + (1.0 - rho) * mean);
+            self.smoothings += 1;
+        }
+        None
+    }
+
+    fn stats(&self, stats: &mut SlsStats) {
+        stats.weight_increases += self.increases;
+        stats.weight_smoothings += self.smoothings;
+    }
+}
+
+/// Pure additive weighting scheme.
+pub struct Paws {
+    flat_move_prob: f64,
+    max_increases: u32,
+    candidates: Candidates,
+    /// Weight increases since the last decrease
+    pending: u32,
+    increases: u64,
+    smoothings: u64,
+}
+
+impl Paws {
+    pub fn new(n_vars: usize, flat_move_prob: f64, max_increases: u32) -> Self {
+        Paws {
+            flat_move_prob,
+            max_increases,
+            candidates: Candidates::new(n_vars),
+            pending: 0,
+            increases: 0,
+            smoothings: 0,
+        }
+    }
+}
+
+impl Strategy for Paws {
+    fn reset(&mut self, state: &mut State) {
+        state.init_weights(1.0);
+        self.pending = 0;
+    }
+
+    fn pick_var(&mut self, state: &mut State, rng: &mut dyn RngCore) -> Option<usize> {
+        if let Some(v) = weighted_move(&mut self.candidates, state, rng, self.flat_move_prob) {
+            return Some(v);
+        }
+
+        increase_unsat_weights(state, 1.0);
+        self.increases += 1;
+        self.pending += 1;
+        if self.pending >= self.max_increases {
+            self.pending = 0;
+            state.map_weights(|weight| if weight > 1.0 { weight - 1.0 } else { weight });
+            self.smoothings += 1;
+        }
+        None
+    }
+
+    fn stats(&self, stats: &mut SlsStats) {
+        stats.weight_increases += self.increases;
+        stats.weight_smoothings += self.smoothings;
+    }
+}
+
+/// Divide and distribute fixed weights.
+pub struct Ddfw {
+    init_weight: f64,
+    flat_move_prob: f64,
+    candidates: Candidates,
+    unsat: Vec<usize>,
+    increases: u64,
+    transferred: f64,
+}
+
+impl Ddfw {
+    pub fn new(n_vars: usize, init_weight: f64, flat_move_prob: f64) -> Self {
+        Ddfw {
+            init_weight,
+            flat_move_prob,
+            candidates: Candidates::new(n_vars),
+            unsat: vec![],
+            increases: 0,
+            transferred: 0.0,
+        }
+    }
+
+    /// Heaviest satisfied clause sharing a literal with the clause `c`, or
+    /// if it is lighter than the initial weight, a random satisfied clause
+    /// heavier than the initial weight.
+    fn donor(&self, state: &State, c: usize, rng: &mut dyn RngCore) -> Option<usize> {
+        let weights = state.weights();
+        let mut donor = None;
+        for &lit in state.clause(c) {
+            for &d in state.occurrences(lit) {
+                let heavier = match donor {
+                    Some(donor) => weights[d] > weights[donor],
+                    None => true,
+                };
+                if state.is_sat(d) && heavier {
+                    donor = Some(d);
+                }
+            }
+        }
+        match donor {
+            Some(d) if weights[d] >= self.init_weight => Some(d),
+            _ => (0..DONOR_SAMPLES)
+                .map(|_| rng.gen_range(0, state.n_clauses()))
+                .find(|&d| state.is_sat(d) && weights[d] > self.init_weight)
+                .or(donor),
+        }
+    }
+}
+
+impl Strategy for Ddfw {
+    fn reset(&mut self, state: &mut State) {
+        state.init_weights(self.init_weight);
+    }
+
+    fn pick_var(&mut self, state: &mut State, rng: &mut dyn RngCore) -> Option<usize> {
+        if let Some(v) = weighted_move(&mut self.candidates, state, rng, self.flat_move_prob) {
+            return Some(v);
+        }
+
+        self.unsat.clear();
+        self.unsat.extend_from_slice(state.unsat());
+        for i in 0..self.unsat.len() {
+            let c = self.unsat[i];
+            let d = match self.donor(state, c, rng) {
+                Some(d) => d,
+                None => continue,
+            };
+            // Heavy donors give twice as much, the donor keeping some weight
+            let amount = if state.weights()[d] > self.init_weight {
+                self.init_weight / 4.0
+            } else {
+                self.init_weight / 8.0
+            };
+            if state.weights()[d] <= amount {
+                continue;
+            }
+            state.set_weight(d, state.weights()[d] - amount);
+            state.set_weight(c, state.weights()[c] + amount);
+            self.transferred += amount;
+        }
+        self.increases += 1;
+        None
+    }
+
+    fn stats(&self, stats: &mut SlsStats) {
+        stats.weight_increases += self.increases;
+        stats.weight_transferred += self.transferred;
+    }
+}
@@ -0,0 +1,148 @@
+mod common;
+
+use common::{lit, lits, options, random_formula, solver};
+use rand::prelude::*;
+use solhop_types::{Lit, Solution, Var};
+
+/// Random assumptions, possibly repeated or contradictory.
+fn random_assumptions(n_vars: usize, rng: &mut StdRng) -> Vec<Lit> {
+    (0..rng.gen_range(1, 6))
+        .map(|_| Lit::new(Var::new(rng.gen_range(0, n_vars)), rng.gen()))
+        .collect()
+}
+
+/// Whether the clauses with the assumptions as unit clauses are satisfiable,
+/// by a solver without assumptions.
+fn is_sat(n_vars: usize, clauses: &[Vec<Lit>], assumptions: &[Lit]) -> bool {
+    let mut solver = solver(n_vars, clauses, options());
+    for &lit in assumptions {
+        solver.add_clause(vec![lit]);
+    }
+    matches!(solver.solve(vec![]), Solution::Sat(_))
+}
+
+fn assert_satisfies(model: &[bool], assumptions: &[Lit]) {
+    for lit in assumptions {
+        assert_eq!(model[lit.var().index()], !lit.sign());
+    }
+}
+
+#[test]
+fn incremental_results_match_unit_clauses() {
+    let mut n_unsat = 0;
+    for seed in 0..30 {
+        let mut rng = StdRng::seed_from_u64(seed);
+        let clauses = random_formula(20, 60, 2..4, seed);
+        // Self-check mode verifies the models against the assumptions
+        let mut solver = solver(20, &clauses, options());
+        for _ in 0..10 {
+            let assumptions = random_assumptions(20, &mut rng);
+            match solver.solve(assumptions.clone()) {
+                Solution::Sat(model) => {
+                    assert!(is_sat(20, &clauses, &assumptions));
+                    assert_satisfies(&model, &assumptions);
+                }
+                Solution::Unsat => {
+                    assert!(!is_sat(20, &clauses, &assumptions));
+                    n_unsat += 1;
+                }
+                other => panic!("unexpected solution: {:?}", other),
+            }
+        }
+    }
+    assert!(n_unsat > 0);
+}
+
+#[test]
+fn assumptions_implied_at_level_0() {
+    let mut solver = solver(
+        3,
+        &[lits(&[1]), lits(&[-1, 2]), lits(&[-2, 3, -1])],
+        options(),
+    );
+    match solver.solve(lits(&[2, 3])) {
+        Solution::Sat(model) => assert_eq!(model, vec![true, true, true]),
+        other => panic!("expected a model, got {:?}", other),
+    }
+    assert_eq!(solver.solve(lits(&[3, -2])), Solution::Unsat);
+    assert_eq!(solver.failed_assumptions(), &[lit(-2)][..]);
+    assert_eq!(solver.solve(lits(&[-3])), Solution::Unsat);
+    assert!(matches!(solver.solve(lits(&[1])), Solution::Sat(_)));
+}
+
+#[test]
+fn repeated_and_contradictory_assumptions() {
+    let mut solver = solver(2, &[lits(&[1, 2])], options());
+    match solver.solve(lits(&[-1, -1])) {
+        Solution::Sat(model) => assert_eq!(model, vec![false, true]),
+        other => panic!("expected a model, got {:?}", other),
+    }
+    assert_eq!(solver.solve(lits(&[2, -1, 1])), Solution::Unsat);
+    let mut failed = solver.failed_assumptions().to_vec();
+    failed.sort_by_key(|lit| lit.index());
+    assert_eq!(failed, lits(&[1, -1]));
+    assert_eq!(solver.solve(lits(&[-1, -2, -1])), Solution::Unsat);
+    assert!(matches!(solver.solve(vec![]), Solution::Sat(_)));
+}
+
+#[test]
+fn failed_assumptions_are_an_unsat_core() {
+    let mut n_cores = 0;
+    for seed in 0..30 {
+        let mut rng = StdRng::seed_from_u64(seed);
+        let clauses = random_formula(20, 60, 2..4, seed);
+        let mut solver = solver(20, &clauses, options());
+        for _ in 0..10 {
+            let assumptions = random_assumptions(20, &mut rng);
+            if solver.solve(assumptions.clone()) != Solution::Unsat {
+                assert!(solver.failed_assumptions().is_empty());
+                continue;
+            }
+            let failed = solver.failed_assumptions();
+            assert!(failed.iter().all(|lit| assumptions.contains(lit)));
+            assert!(!is_sat(20, &clauses, failed));
+            n_cores += !failed.is_empty() as usize;
+        }
+    }
+    assert!(n_cores > 0);
+}
+
+#[test]
+fn failed_assumptions_are_empty_if_unsat_without_assumptions() {
+    let mut solver = solver(2, &[lits(&[1, 2]), lits(&[1, -2]), lits(&[-1])], options());
+    assert_eq!(solver.solve(lits(&[2])), Solution::Unsat);
+    assert!(solver.failed_assumptions().is_empty());
+    assert_eq!(solver.solve(vec![]), Solution::Unsat);
+    assert!(solver.failed_assumptions().is_empty());
+}
+
+#[test]
+fn failed_assumptions_are_cleared_by_the_next_call() {
+    let mut solver = solver(3, &[lits(&[-1, 2]), lits(&[-2, 3])], options());
+    assert_eq!(solver.solve(lits(&[1, -3])), Solution::Unsat);
+    assert!(!solver.failed_assumptions().is_empty());
+    assert!(matches!(solver.solve(lits(&[1])), Solution::Sat(_)));
+    assert!(solver.failed_assumptions().is_empty());
+}
+
+#[test]
+fn self_checked_results_are_ok() {
+    for seed in 0..20 {
+        let clauses = random_formula(20, 60, 2..4, seed);
+        let mut solver = solver(20, &clauses, options());
+        let expected = is_sat(20, &clauses, &[]);
+        match solver.try_solve(vec![]) {
+            Ok(Solution::Sat(_)) => assert!(expected),
+            Ok(Solution::Unsat) => assert!(!expected),
+            other => panic!("unexpected result: {:?}", other),
+        }
+    }
+
+    // Unsatisfiable under the assumptions only
+    let mut solver = solver(3, &[lits(&[-1, 2]), lits(&[-2, 3])], options());
+    assert_eq!(solver.try_solve(lits(&[1, -3])), Ok(Solution::Unsat));
+    match solver.try_solve(lits(&[1])) {
+        Ok(Solution::Sat(model)) => assert_eq!(model, vec![true, true, true]),
+        other => panic!("unexpected result: {:?}", other),
+    }
+}
@@ -0,0 +1,100 @@
+//! Helpers shared by the integration tests.
+#![allow(dead_code)]
+
+use rand::prelude::*;
+use rsat::cdcl::{Solver, SolverOptions};
+use solhop_types::{Clause, Lit, Var};
+use std::ops::Range;
+
+/// Literal in DIMACS notation.
+pub fn lit(x: i64) -> Lit {
+    Lit::new(Var::new(x.unsigned_abs() as usize - 1), x < 0)
+}
+
+pub fn lits(xs: &[i64]) -> Vec<Lit> {
+    xs.iter().map(|&x| lit(x)).collect()
+}
+
+/// Random formula with clause lengths in the given range.
+pub fn random_formula(
+    n_vars: usize,
+    n_clauses: usize,
+    lens: Range<usize>,
+    seed: u64,
+) -> Vec<Vec<Lit>> {
+    let mut rng = StdRng::seed_from_u64(seed);
+    (0..n_clauses)
+        .map(|_| {
+            (0..rng.gen_range(lens.start, lens.end))
+                .map(|_| Lit::new(Var::new(rng.gen_range(0, n_vars)), rng.gen()))
+                .collect()
+        })
+        .collect()
+}
+
+/// Default options with the models checked against the clauses.
+pub fn options() -> SolverOptions {
+    SolverOptions {
+        self_check: true,
+        ..SolverOptions::default()
+    }
+}
+
+pub fn solver(n_vars: usize, clauses: &[Vec<Lit>], options: SolverOptions) -> Solver {
+    let mut solver = Solver::new(options);
+    solver.new_vars(n_vars);
+    for clause in clauses {
+        solver.add_clause(clause.clone());
+    }
+    solver
+}
+
+/// Number of variables used by the clauses.
+pub fn n_vars(clauses: &[Vec<Lit>]) -> usize {
+    clauses
+        .iter()
+        .flatten()
+        .map(|lit| lit.var().index() + 1)
+        .max()
+        .unwrap_or(0)
+}
+
+/// Inverter chain `x_{i+1} = !x_i`, with `x_0 | x_1 | x_2` and `x_0 | x_2 | x_3`.
+pub fn inverter_chain(n: usize) -> Vec<Vec<Lit>> {
+    let mut clauses: Vec<Vec<Lit>> = (0..n - 1)
+        .flat_map(|i| {
+            let (x, y) = (Var::new(i), Var::new(i + 1));
+            vec![
+                vec![x.pos_lit(), y.pos_lit()],
+                vec![x.neg_lit(), y.neg_lit()],
+            ]
+        })
+        .collect();
+    clauses.push((0..3).map(|i| Var::new(i).pos_lit()).collect());
+    clauses.push([0, 2, 3].iter().map(|&i| Var::new(i).pos_lit()).collect());
+    clauses
+}
+
+/// Pigeonhole formula, unsatisfiable if there are more pigeons than holes.
+/// Variable `i * n_holes + j` is true if pigeon `i` is in hole `j`.
+pub fn pigeonhole(n_pigeons: usize, n_holes: usize) -> Vec<Vec<Lit>> {
+    let p = |i: usize, j: usize| Var::new(i * n_holes + j);
+    let mut clauses: Vec<Vec<Lit>> = (0..n_pigeons)
+        .map(|i| (0..n_holes).map(|j| p(i, j).pos_lit()).collect())
+        .collect();
+    for j in 0..n_holes {
+        for i in 0..n_pigeons {
+            for k in i + 1..n_pigeons {
+                clauses.push(vec![p(i, j).neg_lit(), p(k, j).neg_lit()]);
+            }
+        }
+    }
+    clauses
+}
+
+pub fn formula(clauses: &[Vec<Lit>]) -> Vec<Clause> {
+    clauses
+        .iter()
+        .map(|lits| Clause { lits: lits.clone() })
+        .collect()
+}
@@ -0,0 +1,139 @@
+mod common;
+
+use common::{inverter_chain, lits, random_formula, solver};
+use rsat::cdcl::{InprocessOptions, Solver, SolverOptions};
+use solhop_types::{Lit, Solution, Var};
+
+/// Self-checked options with probing at every 10 conflicts.
+fn options() -> SolverOptions {
+    let default = common::options();
+    SolverOptions {
+        inprocess: InprocessOptions {
+            interval: 10,
+            probing: true,
+            ..default.inprocess
+        },
+        ..default
+    }
+}
+
+fn solve(clauses: &[&[i64]]) -> (Solution, Solver) {
+    let clauses: Vec<Vec<Lit>> = clauses.iter().map(|clause| lits(clause)).collect();
+    let mut solver = solver(common::n_vars(&clauses), &clauses, options());
+    (solver.solve(vec![]), solver)
+}
+
+#[test]
+fn failed_literals_are_learnt() {
+    let (solution, solver) = solve(&[&[-1, 2], &[-1, 3], &[-1, -2, -3], &[1, 4, 5]]);
+    match solution {
+        Solution::Sat(model) => assert!(!model[0]),
+        other => panic!("expected a model, got {:?}", other),
+    }
+    assert!(solver.stats().failed_literals > 0);
+}
+
+#[test]
+fn necessary_assignments_are_learnt() {
+    let (solution, solver) = solve(&[&[-1, 2], &[1, 2], &[-2, 3, 4]]);
+    assert!(matches!(solution, Solution::Sat(_)));
+    assert!(solver.stats().necessary_assignments > 0);
+}
+
+#[test]
+fn hyper_binary_resolvents_are_added() {
+    let (solution, solver) = solve(&[&[-1, 2], &[-1, 3], &[-2, -3, 4], &[-4, 5, 6]]);
+    assert!(matches!(solution, Solution::Sat(_)));
+    assert!(solver.stats().hyper_binary_resolvents > 0);
+}
+
+#[test]
+fn hyper_binary_resolvents_use_the_dominator() {
+    // 2 dominates 3 and 4, so probing 1 or 2 gives `-2 5` only
+    let (solution, solver) = solve(&[&[-1, 2], &[-2, 3], &[-2, 4], &[-3, -4, 5]]);
+    assert!(matches!(solution, Solution::Sat(_)));
+    assert_eq!(solver.stats().hyper_binary_resolvents, 1);
+}
+
+#[test]
+fn repeated_probing_does_not_grow_the_clause_db() {
+    let options = || SolverOptions {
+        inprocess: InprocessOptions {
+            interval: 0,
+            ..options().inprocess
+        },
+        ..options()
+    };
+    let mut resolvents = 0;
+    for seed in 0..20 {
+        let clauses = random_formula(100, 150, 2..5, seed);
+        let mut solver = solver(100, &clauses, options());
+        solver.solve(vec![]);
+        solver.solve(vec![]);
+        let n_resolvents = solver.stats().hyper_binary_resolvents;
+        let n_learnts = solver.n_learnts() as i64 - solver.stats().conflicts as i64;
+        // Each call probes again, with the resolvents of the previous calls
+        for _ in 0..5 {
+            solver.solve(vec![]);
+            assert_eq!(solver.stats().hyper_binary_resolvents, n_resolvents);
+            assert!(solver.n_learnts() as i64 - solver.stats().conflicts as i64 <= n_learnts);
+        }
+        resolvents += n_resolvents;
+    }
+    assert!(resolvents > 0);
+}
+
+#[test]
+fn equivalent_literals_are_substituted() {
+    let n = 20;
+    let options = SolverOptions {
+        inprocess: InprocessOptions {
+            probing: false,
+            substitution: true,
+            ..options().inprocess
+        },
+        ..options()
+    };
+    let mut solver = solver(n, &inverter_chain(n), options);
+    assert!(matches!(solver.solve(vec![]), Solution::Sat(_)));
+    assert!(solver.stats().substituted_vars > 0);
+
+    // Assumptions and clauses on substituted variables
+    let x = |i: usize| Var::new(i);
+    assert_eq!(
+        solver.solve(vec![x(0).pos_lit(), x(10).neg_lit()]),
+        Solution::Unsat
+    );
+    assert_eq!(
+        solver.solve(vec![x(0).pos_lit(), x(11).pos_lit()]),
+        Solution::Unsat
+    );
+    solver.add_clause(vec![x(5).neg_lit()]);
+    match solver.solve(vec![]) {
+        Solution::Sat(model) => {
+            assert!(model
+                .iter()
+                .enumerate()
+                .all(|(i, &value)| value == (i % 2 == 0)))
+        }
+        other => panic!("expected a model, got {:?}", other),
+    }
+}
+
+#[test]
+fn vivification_shortens_clauses() {
+    let options = SolverOptions {
+        inprocess: InprocessOptions {
+            probing: false,
+            vivification: true,
+            ..options().inprocess
+        },
+        ..options()
+    };
+    // -1 implies 2, so the last clause is shortened
+    let clauses = vec![lits(&[1, 3]), lits(&[-3, 2]), lits(&[1, 2, 4, 5])];
+    let mut solver = solver(5, &clauses, options);
+    assert!(matches!(solver.solve(vec![]), Solution::Sat(_)));
+    assert_eq!(solver.stats().vivified_clauses, 1);
+    assert!(solver.stats().vivified_literals > 0);
+}
@@ -0,0 +1,110 @@
+mod common;
+
+use common::pigeonhole;
+use rsat::cdcl::{Solver, SolverOptions};
+use rsat::limits::Limits;
+use rsat::sls::{self, SlsOptions};
+use solhop_types::Solution;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Arc;
+use std::thread;
+use std::time::{Duration, Instant};
+
+/// `n + 1` pigeons in `n` holes.
+fn cdcl_solver(n: usize, limits: Limits) -> Solver {
+    let mut solver = Solver::new(SolverOptions {
+        limits,
+        ..SolverOptions::default()
+    });
+    solver.new_vars((n + 1) * n);
+    for clause in pigeonhole(n + 1, n) {
+        solver.add_clause(clause);
+    }
+    solver
+}
+
+#[test]
+fn cdcl_stops_at_conflict_and_propagation_limits() {
+    let mut solver = cdcl_solver(
+        5,
+        Limits {
+            conflicts: Some(10),
+            ..Limits::default()
+        },
+    );
+    assert_eq!(solver.solve(vec![]), Solution::Unknown);
+    let conflicts = solver.stats().conflicts;
+    assert!((10..20).contains(&conflicts));
+
+    // Limits are counted from the start of each call
+    assert_eq!(solver.solve(vec![]), Solution::Unknown);
+    assert!(solver.stats().conflicts >= conflicts + 10);
+
+    solver.set_limits(Limits {
+        propagations: Some(100),
+        ..Limits::default()
+    });
+    assert_eq!(solver.solve(vec![]), Solution::Unknown);
+
+    solver.set_limits(Limits::default());
+    assert_eq!(solver.solve(vec![]), Solution::Unsat);
+}
+
+#[test]
+fn cdcl_stops_at_time_limit() {
+    let mut solver = cdcl_solver(
+        12,
+        Limits {
+            time: Some(Duration::from_millis(100)),
+            ..Limits::default()
+        },
+    );
+    let start = Instant::now();
+    assert_eq!(solver.solve(vec![]), Solution::Unknown);
+    assert!(start.elapsed() < Duration::from_secs(10));
+}
+
+#[test]
+fn cdcl_is_interrupted_from_another_thread() {
+    let interrupt = Arc::new(AtomicBool::new(false));
+    let mut solver = cdcl_solver(
+        12,
+        Limits {
+            interrupt: Some(interrupt.clone()),
+            ..Limits::default()
+        },
+    );
+    let handle = thread::spawn(move || solver.solve(vec![]));
+    thread::sleep(Duration::from_millis(50));
+    interrupt.store(true, Ordering::Relaxed);
+    assert_eq!(handle.join().unwrap(), Solution::Unknown);
+}
+
+#[test]
+fn sls_stops_at_flip_limit_and_interrupt() {
+    let mut solver = sls::Solver::new_from_buf_reader(&mut "p cnf 30 0\n".as_bytes());
+    for clause in pigeonhole(6, 5) {
+        solver.add_clause(clause);
+    }
+
+    let solution = solver.solve(&SlsOptions {
+        seed: Some(0),
+        limits: Limits {
+            flips: Some(1000),
+            ..Limits::default()
+        },
+        ..SlsOptions::default()
+    });
+    assert!(matches!(solution, Solution::Best(_)));
+    assert_eq!(solver.stats().flips, 1000);
+
+    let solution = solver.solve(&SlsOptions {
+        limits: Limits {
+            interrupt: Some(Arc::new(AtomicBool::new(true))),
+            ..Limits::default()
+        },
+        ..SlsOptions::default()
+    });
+    assert!(matches!(solution, Solution::Best(_)));
+    assert_eq!(solver.stats().flips, 0);
+}
@@ -0,0 +1,146 @@
+mod common;
+
+use common::{random_formula, solver};
+use rand::prelude::*;
+use rsat::cdcl::{PreprocessOptions, SolverOptions};
+use solhop_types::{Lit, Solution, Var};
+
+/// Self-checked options with variable elimination.
+fn options() -> SolverOptions {
+    let default = common::options();
+    SolverOptions {
+        preprocess: PreprocessOptions {
+            elimination: true,
+            ..default.preprocess
+        },
+        ..default
+    }
+}
+
+/// Implications `x_i -> x_{i+1}`.
+fn chain(n: usize) -> Vec<Vec<Lit>> {
+    (0..n - 1)
+        .map(|i| vec![Var::new(i).neg_lit(), Var::new(i + 1).pos_lit()])
+        .collect()
+}
+
+#[test]
+fn models_are_extended_to_eliminated_vars() {
+    let mut eliminated = 0;
+    for seed in 0..50 {
+        let clauses = random_formula(30, 60, 1..5, seed);
+        let mut with_elim = solver(30, &clauses, options());
+        let mut without_elim = solver(30, &clauses, SolverOptions::default());
+        // Self-check mode verifies the models
+        let solution = with_elim.solve(vec![]);
+        match without_elim.solve(vec![]) {
+            Solution::Sat(_) => assert!(matches!(solution, Solution::Sat(_))),
+            other => assert_eq!(solution, other),
+        }
+        eliminated += with_elim.stats().eliminated_vars;
+    }
+    assert!(eliminated > 0);
+}
+
+#[test]
+fn frozen_vars_are_not_eliminated() {
+    let n = 20;
+    let mut solver = solver(n, &chain(n), options());
+    solver.set_frozen(Var::new(0), true);
+    solver.set_frozen(Var::new(n - 1), true);
+    assert!(matches!(solver.solve(vec![]), Solution::Sat(_)));
+    assert_eq!(solver.stats().eliminated_vars, n as u64 - 2);
+
+    let first = Var::new(0).pos_lit();
+    let last = Var::new(n - 1).neg_lit();
+    assert_eq!(solver.solve(vec![first, last]), Solution::Unsat);
+    assert_eq!(solver.stats().eliminated_vars, n as u64 - 2);
+}
+
+#[test]
+fn eliminated_vars_can_be_used_again() {
+    let n = 20;
+    let mut solver = solver(n, &chain(n), options());
+    assert!(matches!(solver.solve(vec![]), Solution::Sat(_)));
+    assert!(solver.stats().eliminated_vars > 0);
+
+    // Assumptions on eliminated variables
+    let middle = Var::new(n / 2);
+    assert_eq!(
+        solver.solve(vec![middle.pos_lit(), Var::new(n - 1).neg_lit()]),
+        Solution::Unsat
+    );
+    assert!(!solver.failed_assumptions().is_empty());
+
+    // Clauses on eliminated variables
+    solver.add_clause(vec![Var::new(0).pos_lit()]);
+    match solver.solve(vec![]) {
+        Solution::Sat(model) => assert!(model.iter().all(|&value| value)),
+        other => panic!("expected a model, got {:?}", other),
+    }
+    assert_eq!(solver.solve(vec![middle.neg_lit()]), Solution::Unsat);
+}
+
+/// Tseitin encoding of a random circuit of and/or gates over `n_inputs`
+/// inputs, with its output asserted. Returns the number of variables.
+fn circuit(n_inputs: usize, n_gates: usize, seed: u64) -> (usize, Vec<Vec<Lit>>) {
+    let mut rng = StdRng::seed_from_u64(seed);
+    let mut clauses = vec![];
+    for g in n_inputs..n_inputs + n_gates {
+        let out = Var::new(g).pos_lit();
+        let a = Lit::new(Var::new(rng.gen_range(0, g)), rng.gen());
+        let b = Lit::new(Var::new(rng.gen_range(0, g)), rng.gen());
+        if rng.gen() {
+            clauses.push(vec![!out, a]);
+            clauses.push(vec![!out, b]);
+            clauses.push(vec![out, !a, !b]);
+        } else {
+            clauses.push(vec![out, !a]);
+            clauses.push(vec![out, !b]);
+            clauses.push(vec![!out, a, b]);
+        }
+    }
+    let n_vars = n_inputs + n_gates;
+    clauses.push(vec![Var::new(n_vars - 1).pos_lit()]);
+    (n_vars, clauses)
+}
+
+#[test]
+fn blocked_and_covered_clauses_are_removed() {
+    let mut blocked = 0;
+    let mut covered = 0;
+    for seed in 0..20 {
+        let (n_vars, clauses) = circuit(10, 40, seed);
+        let options = SolverOptions {
+            preprocess: PreprocessOptions {
+                elimination: false,
+                blocked_clauses: true,
+                covered_clauses: seed % 2 == 0,
+                ..options().preprocess
+            },
+            ..options()
+        };
+        let mut with_bce = solver(n_vars, &clauses, options);
+        let mut without_bce = solver(n_vars, &clauses, SolverOptions::default());
+        let solution = with_bce.solve(vec![]);
+        match without_bce.solve(vec![]) {
+            Solution::Sat(_) => assert!(matches!(solution, Solution::Sat(_))),
+            other => assert_eq!(solution, other),
+        }
+        blocked += with_bce.stats().blocked_clauses;
+        covered += with_bce.stats().covered_clauses;
+
+        // Clauses removed on the variables of the assumptions are added back
+        let assumptions: Vec<Lit> = (0..3).map(|v| Var::new(v).neg_lit()).collect();
+        let solution = with_bce.solve(assumptions.clone());
+        for lit in assumptions {
+            without_bce.add_clause(vec![lit]);
+        }
+        match without_bce.solve(vec![]) {
+            Solution::Sat(_) => assert!(matches!(solution, Solution::Sat(_))),
+            other => assert_eq!(solution, other),
+        }
+    }
+    assert!(blocked > 0);
+    assert!(covered > 0);
+}
@@ -0,0 +1,403 @@
+mod common;
+
+use common::{formula, inverter_chain, lit, pigeonhole, random_formula};
+use rand::prelude::*;
+use rsat::cdcl::{
+    DratClause, InprocessOptions, LearntMinimization, PreprocessOptions, ProofFormat, ProofOutput,
+    Solver, SolverOptions, SolverStats,
+};
+use rsat::proof::{check_drat, check_lrat, read_drat, read_lrat, ProofError};
+use solhop_types::{Clause, Lit, Solution, Var};
+use std::io::{self, Write};
+use std::sync::{Arc, Mutex};
+
+/// Writer sharing its buffer with the test.
+#[derive(Clone, Default)]
+struct SharedBuf(Arc<Mutex<Vec<u8>>>);
+
+impl Write for SharedBuf {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        self.0.lock().unwrap().extend_from_slice(buf);
+        Ok(buf.len())
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        Ok(())
+    }
+}
+
+fn clause(lits: &[i64]) -> Clause {
+    Clause {
+        lits: common::lits(lits),
+    }
+}
+
+/// Random 3-SAT formula with a high clause to variable ratio.
+fn random_3sat(n_vars: usize, seed: u64) -> (usize, Vec<Clause>) {
+    let mut rng = StdRng::seed_from_u64(seed);
+    let clauses = (0..n_vars * 6)
+        .map(|_| Clause {
+            lits: (0..3)
+                .map(|_| Lit::new(Var::new(rng.gen_range(0, n_vars)), rng.gen()))
+                .collect(),
+        })
+        .collect();
+    (n_vars, clauses)
+}
+
+fn unsat_formulas() -> Vec<(usize, Vec<Clause>)> {
+    let mut formulas = vec![
+        (1, vec![clause(&[1]), clause(&[-1])]),
+        (
+            2,
+            vec![
+                clause(&[1, 2]),
+                clause(&[-1, 2]),
+                clause(&[1, -2]),
+                clause(&[-1, -2]),
+            ],
+        ),
+    ];
+    formulas.extend((2..6).map(|n| ((n + 1) * n, formula(&pigeonhole(n + 1, n)))));
+    formulas.extend((0..10).map(|seed| random_3sat(40, seed)));
+    formulas
+}
+
+fn solve(n_vars: usize, clauses: &[Clause], options: SolverOptions) -> Solver {
+    let clauses: Vec<Vec<Lit>> = clauses.iter().map(|clause| clause.lits.clone()).collect();
+    let mut solver = common::solver(n_vars, &clauses, options);
+    assert_eq!(solver.solve(vec![]), Solution::Unsat);
+    solver
+}
+
+fn solve_with_output(
+    n_vars: usize,
+    clauses: &[Clause],
+    format: ProofFormat,
+    options: SolverOptions,
+) -> Vec<u8> {
+    let buf = SharedBuf::default();
+    let options = SolverOptions {
+        proof_output: Some(ProofOutput {
+            writer: Box::new(buf.clone()),
+            format,
+        }),
+        ..options
+    };
+    let mut solver = solve(n_vars, clauses, options);
+    solver.flush_proof().unwrap();
+    let data = buf.0.lock().unwrap().clone();
+    data
+}
+
+#[test]
+fn captured_drat_proofs_are_verified() {
+    for (n_vars, clauses) in unsat_formulas() {
+        let options = SolverOptions {
+            capture_drat: true,
+            ..Default::default()
+        };
+        let proof = solve(n_vars, &clauses, options).drat_clauses().unwrap();
+        assert_eq!(check_drat(&clauses, &proof), Ok(()));
+    }
+}
+
+#[test]
+fn streamed_drat_proofs_are_verified() {
+    for format in [ProofFormat::Drat, ProofFormat::BinaryDrat].iter() {
+        for (n_vars, clauses) in unsat_formulas() {
+            let data = solve_with_output(n_vars, &clauses, *format, Default::default());
+            let proof = read_drat(&mut data.as_slice(), *format).unwrap();
+            assert_eq!(check_drat(&clauses, &proof), Ok(()));
+        }
+    }
+}
+
+#[test]
+fn lrat_proofs_are_verified() {
+    for (n_vars, clauses) in unsat_formulas() {
+        let data = solve_with_output(n_vars, &clauses, ProofFormat::Lrat, Default::default());
+        let proof = read_lrat(&mut data.as_slice()).unwrap();
+        assert_eq!(check_lrat(&clauses, &proof), Ok(()));
+    }
+}
+
+#[test]
+fn proofs_with_learnt_minimization_are_verified() {
+    for minimization in [LearntMinimization::Local, LearntMinimization::Recursive].iter() {
+        let mut minimized = 0;
+        for (n_vars, clauses) in unsat_formulas() {
+            let options = SolverOptions {
+                capture_drat: true,
+                learnt_minimization: *minimization,
+                ..Default::default()
+            };
+            let solver = solve(n_vars, &clauses, options);
+            minimized += solver.stats().minimized_literals;
+            let proof = solver.drat_clauses().unwrap();
+            assert_eq!(check_drat(&clauses, &proof), Ok(()));
+
+            let options = SolverOptions {
+                learnt_minimization: *minimization,
+                ..Default::default()
+            };
+            let data = solve_with_output(n_vars, &clauses, ProofFormat::Lrat, options);
+            let proof = read_lrat(&mut data.as_slice()).unwrap();
+            assert_eq!(check_lrat(&clauses, &proof), Ok(()));
+        }
+        assert!(minimized > 0, "{:?}", minimization);
+    }
+}
+
+/// Simplification pass, with the formulas it is checked on and the counter
+/// showing that it ran.
+struct Simplification {
+    name: &'static str,
+    preprocess: PreprocessOptions,
+    inprocess: InprocessOptions,
+    formula: fn(u64) -> Vec<Vec<Lit>>,
+    counter: fn(&SolverStats) -> u64,
+}
+
+fn simplifications() -> Vec<Simplification> {
+    let SolverOptions {
+        preprocess,
+        inprocess,
+        ..
+    } = SolverOptions::default();
+    vec![
+        Simplification {
+            name: "elimination",
+            preprocess: PreprocessOptions {
+                elimination: true,
+                ..preprocess
+            },
+            inprocess,
+            formula: |seed| random_formula(20, 80, 2..5, seed),
+            counter: |stats| stats.eliminated_vars,
+        },
+        Simplification {
+            name: "elimination and covered clauses",
+            preprocess: PreprocessOptions {
+                elimination: true,
+                covered_clauses: true,
+                ..preprocess
+            },
+            inprocess,
+            formula: |seed| random_formula(20, 80, 2..5, seed),
+            counter: |stats| stats.covered_clauses,
+        },
+        Simplification {
+            name: "blocked and covered clauses",
+            preprocess: PreprocessOptions {
+                blocked_clauses: true,
+                covered_clauses: true,
+                ..preprocess
+            },
+            inprocess,
+            formula: |seed| random_formula(20, 80, 2..5, seed),
+            counter: |stats| stats.blocked_clauses + stats.covered_clauses,
+        },
+        Simplification {
+            name: "probing",
+            preprocess,
+            inprocess: InprocessOptions {
+                interval: 10,
+                probing: true,
+                ..inprocess
+            },
+            formula: |seed| random_formula(40, 120, 2..4, seed),
+            counter: |stats| stats.failed_literals + stats.necessary_assignments,
+        },
+        Simplification {
+            name: "substitution",
+            preprocess,
+            inprocess: InprocessOptions {
+                interval: 10,
+                substitution: true,
+                ..inprocess
+            },
+            formula: |seed| {
+                let mut clauses = random_formula(40, 80, 2..4, seed);
+                clauses.extend(inverter_chain(10));
+                clauses
+            },
+            counter: |stats| stats.substituted_vars,
+        },
+        Simplification {
+            name: "vivification",
+            preprocess,
+            inprocess: InprocessOptions {
+                interval: 10,
+                probing: true,
+                vivification: true,
+                ..inprocess
+            },
+            formula: |seed| random_formula(40, 120, 2..4, seed),
+            counter: |stats| stats.vivified_clauses,
+        },
+    ]
+}
+
+#[test]
+fn drat_proofs_with_simplification_are_verified() {
+    for simplification in simplifications() {
+        let name = simplification.name;
+        let mut n_unsat = 0;
+        let mut n_simplified = 0;
+        for seed in 0..50 {
+            let clauses = (simplification.formula)(seed);
+            let options = SolverOptions {
+                capture_drat: true,
+                preprocess: simplification.preprocess,
+                inprocess: simplification.inprocess,
+                ..common::options()
+            };
+            let mut solver = common::solver(common::n_vars(&clauses), &clauses, options);
+            let solution = solver.solve(vec![]);
+            n_simplified += (simplification.counter)(solver.stats());
+            if solution == Solution::Unsat {
+                n_unsat += 1;
+                let proof = solver.drat_clauses().unwrap();
+                assert_eq!(check_drat(&formula(&clauses), &proof), Ok(()), "{}", name);
+            }
+        }
+        assert!(n_unsat > 0, "{}", name);
+        assert!(n_simplified > 0, "{}", name);
+    }
+}
+
+/// Solver writing an LRAT proof, which is not read.
+fn lrat_solver(options: SolverOptions) -> Solver {
+    Solver::new(SolverOptions {
+        proof_output: Some(ProofOutput {
+            writer: Box::new(io::sink()),
+            format: ProofFormat::Lrat,
+        }),
+        ..options
+    })
+}
+
+#[test]
+#[should_panic(expected = "variable elimination is not supported with LRAT proofs")]
+fn lrat_proofs_reject_elimination() {
+    let default = SolverOptions::default();
+    lrat_solver(SolverOptions {
+        preprocess: PreprocessOptions {
+            elimination: true,
+            ..default.preprocess
+        },
+        ..default
+    });
+}
+
+#[test]
+#[should_panic(expected = "blocked and covered clause elimination are not supported")]
+fn lrat_proofs_reject_blocked_clauses() {
+    let default = SolverOptions::default();
+    lrat_solver(SolverOptions {
+        preprocess: PreprocessOptions {
+            blocked_clauses: true,
+            ..default.preprocess
+        },
+        ..default
+    });
+}
+
+#[test]
+#[should_panic(expected = "blocked and covered clause elimination are not supported")]
+fn lrat_proofs_reject_covered_clauses() {
+    let default = SolverOptions::default();
+    lrat_solver(SolverOptions {
+        preprocess: PreprocessOptions {
+            covered_clauses: true,
+            ..default.preprocess
+        },
+        ..default
+    });
+}
+
+#[test]
+#[should_panic(expected = "failed literal probing is not supported with LRAT proofs")]
+fn lrat_proofs_reject_probing() {
+    let default = SolverOptions::default();
+    lrat_solver(SolverOptions {
+        inprocess: InprocessOptions {
+            probing: true,
+            ..default.inprocess
+        },
+        ..default
+    });
+}
+
+#[test]
+#[should_panic(expected = "equivalent literal substitution is not supported with LRAT proofs")]
+fn lrat_proofs_reject_substitution() {
+    let default = SolverOptions::default();
+    lrat_solver(SolverOptions {
+        inprocess: InprocessOptions {
+            substitution: true,
+            ..default.inprocess
+        },
+        ..default
+    });
+}
+
+#[test]
+#[should_panic(expected = "vivification is not supported with LRAT proofs")]
+fn lrat_proofs_reject_vivification() {
+    let default = SolverOptions::default();
+    lrat_solver(SolverOptions {
+        inprocess: InprocessOptions {
+            vivification: true,
+            ..default.inprocess
+        },
+        ..default
+    });
+}
+
+#[test]
+fn invalid_drat_steps_are_rejected() {
+    let clauses = vec![clause(&[1, 2]), clause(&[-1, 2]), clause(&[-1, -2])];
+    let proof = vec![
+        DratClause::Add(vec![lit(2)]),
+        DratClause::Add(vec![lit(1)]),
+        DratClause::Add(vec![]),
+    ];
+    assert_eq!(
+        check_drat(&clauses, &proof),
+        Err(ProofError::InvalidStep(1))
+    );
+    assert_eq!(
+        check_drat(&clauses, &proof[..1]),
+        Err(ProofError::NoEmptyClause)
+    );
+}
+
+#[test]
+fn rat_steps_are_accepted() {
+    // `-3 1` is not implied, but has no resolvent on `-3`
+    let clauses = vec![clause(&[1, 2]), clause(&[-1, 2]), clause(&[1, -2])];
+    let proof = vec![
+        DratClause::Add(vec![lit(-3), lit(1)]),
+        DratClause::Add(vec![lit(3), lit(-1), lit(-2)]),
+    ];
+    assert_eq!(check_drat(&clauses, &proof), Err(ProofError::NoEmptyClause));
+}
+
+#[test]
+fn invalid_lrat_hints_are_rejected() {
+    let clauses = vec![
+        clause(&[1, 2]),
+        clause(&[-1, 2]),
+        clause(&[1, -2]),
+        clause(&[-1, -2]),
+    ];
+    let proof = read_lrat(&mut "5 2 0 1 2 0\n6 0 5 4 3 0\n".as_bytes()).unwrap();
+    assert_eq!(check_lrat(&clauses, &proof), Ok(()));
+    let proof = read_lrat(&mut "5 2 0 1 2 0\n6 0 5 3 0\n".as_bytes()).unwrap();
+    assert_eq!(
+        check_lrat(&clauses, &proof),
+        Err(ProofError::InvalidStep(1))
+    );
+}
@@ -0,0 +1,78 @@
+mod common;
+
+use common::{formula, options, pigeonhole, random_formula, solver};
+use rsat::cdcl::{ClauseDbOptions, ReductionPolicy, SolverOptions};
+use rsat::proof::check_drat;
+use solhop_types::{Lit, Solution};
+
+fn policies() -> Vec<ReductionPolicy> {
+    vec![
+        ReductionPolicy::Activity,
+        ReductionPolicy::Tiered {
+            core_lbd: 2,
+            tier2_lbd: 6,
+        },
+        ReductionPolicy::Tiered {
+            core_lbd: 1,
+            tier2_lbd: 2,
+        },
+    ]
+}
+
+/// Self-checked options capturing the DRAT proof, with the reduction policy.
+fn reduction_options(reduction_policy: ReductionPolicy) -> SolverOptions {
+    let default = options();
+    SolverOptions {
+        clause_db_options: ClauseDbOptions {
+            reduction_policy,
+            ..default.clause_db_options
+        },
+        capture_drat: true,
+        ..default
+    }
+}
+
+/// Solve the formula, checking the proof if it is unsatisfiable.
+/// Returns the solution and the number of learnt clauses removed.
+fn solve(n_vars: usize, clauses: &[Vec<Lit>], policy: ReductionPolicy) -> (Solution, u64) {
+    let mut solver = solver(n_vars, clauses, reduction_options(policy));
+    let solution = solver.solve(vec![]);
+    let removed = solver.stats().conflicts - solver.n_learnts() as u64;
+    if solution == Solution::Unsat {
+        let proof = solver.drat_clauses().unwrap();
+        assert_eq!(check_drat(&formula(clauses), &proof), Ok(()));
+    }
+    (solution, removed)
+}
+
+// Few clauses and many conflicts, so the learnts are reduced and
+// the arena collected many times in each call to solve.
+#[test]
+fn pigeonhole_formulas_with_frequent_reductions() {
+    for policy in policies() {
+        let mut removed = 0;
+        for n in 4..8 {
+            let (solution, r) = solve((n + 1) * n, &pigeonhole(n + 1, n), policy);
+            assert_eq!(solution, Solution::Unsat);
+            removed += r;
+        }
+        assert!(removed > 0, "{:?}", policy);
+    }
+}
+
+#[test]
+fn random_formulas_with_frequent_reductions() {
+    for seed in 0..20 {
+        let clauses = random_formula(60, 256, 3..4, seed);
+        let mut solutions =
+            policies()
+                .into_iter()
+                .map(|policy| match solve(60, &clauses, policy).0 {
+                    Solution::Sat(_) => true,
+                    Solution::Unsat => false,
+                    other => panic!("unexpected solution: {:?}", other),
+                });
+        let first = solutions.next().unwrap();
+        assert!(solutions.all(|sat| sat == first));
+    }
+}
@@ -0,0 +1,173 @@
+use rand::prelude::*;
+use rsat::sls::{Algorithm, ScoreFnType, SlsOptions, Solver};
+use solhop_types::{Lit, Solution, Var};
+use std::cell::Cell;
+use std::rc::Rc;
+
+/// Random 3-SAT formula near the satisfiability threshold, satisfied by a
+/// hidden assignment.
+fn random_3sat(n_vars: usize, seed: u64) -> Solver {
+    let mut rng = StdRng::seed_from_u64(seed);
+    let hidden: Vec<bool> = (0..n_vars).map(|_| rng.gen()).collect();
+    let mut solver = Solver::new_from_buf_reader(&mut format!("p cnf {} 0\n", n_vars).as_bytes());
+    let mut n_clauses = 0;
+    while n_clauses < n_vars * 426 / 100 {
+        let lits: Vec<Lit> = (0..3)
+            .map(|_| Lit::new(Var::new(rng.gen_range(0, n_vars)), rng.gen()))
+            .collect();
+        if lits
+            .iter()
+            .any(|lit| hidden[lit.var().index()] != lit.sign())
+        {
+            solver.add_clause(lits);
+            n_clauses += 1;
+        }
+    }
+    solver
+}
+
+fn algorithms() -> Vec<Algorithm> {
+    vec![
+        Algorithm::ProbSat(ScoreFnType::Poly),
+        Algorithm::ProbSat(ScoreFnType::Exp),
+        Algorithm::WalkSat { noise: 0.567 },
+        Algorithm::NoveltyPlus {
+            noise: 0.5,
+            walk_prob: 0.01,
+        },
+        Algorithm::AdaptiveNoveltyPlus { walk_prob: 0.01 },
+        Algorithm::Gsat { walk_prob: 0.5 },
+        Algorithm::Saps {
+            alpha: 1.3,
+            rho: 0.8,
+            smooth_prob: 0.05,
+            walk_prob: 0.01,
+        },
+        Algorithm::Paws {
+            flat_move_prob: 0.15,
+            max_increases: 10,
+        },
+        Algorithm::Ddfw {
+            init_weight: 8.0,
+            flat_move_prob: 0.15,
+        },
+        Algorithm::Ccanr {
+            smooth_threshold: 300.0,
+            rho: 0.3,
+        },
+    ]
+}
+
+/// Solution and number of flips of a seeded search.
+fn walk(solver: &mut Solver, algorithm: Algorithm, parallel: bool) -> (Solution, u64) {
+    let solution = solver.solve(&SlsOptions {
+        max_tries: 3,
+        max_flips: 2000,
+        algorithm,
+        parallel,
+        seed: Some(42),
+        ..SlsOptions::default()
+    });
+    (solution, solver.stats().flips)
+}
+
+#[test]
+fn same_seed_gives_same_walk() {
+    for seed in 0..4 {
+        let mut solver = random_3sat(150, seed);
+        let n_algorithms = algorithms().len();
+        for i in 0..n_algorithms {
+            let expected = walk(&mut solver, algorithms().remove(i), false);
+            for &n_threads in [1, 4].iter() {
+                let pool = rayon::ThreadPoolBuilder::new()
+                    .num_threads(n_threads)
+                    .build()
+                    .unwrap();
+                let walked = pool.install(|| walk(&mut solver, algorithms().remove(i), true));
+                assert_eq!(walked, expected);
+            }
+        }
+    }
+}
+
+#[test]
+fn models_satisfy_the_formula() {
+    for seed in 0..4 {
+        let mut solver = random_3sat(100, seed);
+        for algorithm in algorithms() {
+            if let Solution::Sat(model) = walk(&mut solver, algorithm, false).0 {
+                assert_eq!(solver.check_model(&model), Ok(()));
+            }
+        }
+    }
+}
+
+#[test]
+fn portfolio_stops_at_first_model() {
+    let mut solver = random_3sat(100, 1);
+    let n_walkers = algorithms().len();
+    let walker = |i| SlsOptions {
+        max_tries: 10,
+        max_flips: 100_000,
+        algorithm: algorithms().remove(i),
+        seed: Some(i as u64),
+        ..SlsOptions::default()
+    };
+    match solver.solve_portfolio(n_walkers, walker) {
+        Solution::Sat(model) => assert_eq!(solver.check_model(&model), Ok(())),
+        solution => panic!("no model found: {:?}", solution),
+    }
+    assert!(solver.stats().tries < n_walkers as u64 * 10);
+}
+
+#[test]
+fn portfolio_returns_best_assignment() {
+    let mut solver = Solver::new_from_buf_reader(&mut "p cnf 2 0\n".as_bytes());
+    for &(a, b) in [(false, false), (false, true), (true, false), (true, true)].iter() {
+        solver.add_clause(vec![Lit::new(Var::new(0), a), Lit::new(Var::new(1), b)]);
+    }
+    let walker = |i| SlsOptions {
+        max_tries: 2,
+        max_flips: 10,
+        seed: Some(i as u64),
+        ..SlsOptions::default()
+    };
+    match solver.solve_portfolio(4, walker) {
+        Solution::Best(model) => assert_eq!(model.len(), 2),
+        solution => panic!("unexpected solution: {:?}", solution),
+    }
+    assert_eq!(solver.stats().tries, 8);
+}
+
+/// ProbSAT polynomial score counting its calls.
+fn counted_score(calls: Rc<Cell<u64>>) -> ScoreFnType {
+    ScoreFnType::Custom(Box::new(move |_, break_count| {
+        calls.set(calls.get() + 1);
+        1.0 / (1.0 + break_count as f32).powf(2.3)
+    }))
+}
+
+#[test]
+fn custom_scores_need_not_be_thread_safe() {
+    let mut solver = random_3sat(100, 0);
+    let calls = Rc::new(Cell::new(0));
+    walk(
+        &mut solver,
+        Algorithm::ProbSat(counted_score(calls.clone())),
+        false,
+    );
+    assert!(calls.get() > 0);
+
+    // Each walk of a portfolio makes its own scoring function
+    let walker = |i| SlsOptions {
+        max_tries: 10,
+        max_flips: 100_000,
+        algorithm: Algorithm::ProbSat(counted_score(Rc::new(Cell::new(0)))),
+        seed: Some(i as u64),
+        ..SlsOptions::default()
+    };
+    match solver.solve_portfolio(4, walker) {
+        Solution::Sat(model) => assert_eq!(solver.check_model(&model), Ok(())),
+        solution => panic!("no model found: {:?}", solution),
+    }
+}